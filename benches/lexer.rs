@@ -0,0 +1,39 @@
+use std::time::{Duration, Instant};
+
+use psudo::lexer::Lexer;
+
+// one chunk of a generated program, mixing every kind of token and some non-ASCII text
+const CHUNK: &str = "let größe_1 = 12.5 * (3 + 4) / 2;\n\
+fun grüß(name) { print \"héllo, \" + name; }\n\
+# a comment with ünïcödé in it\n\
+if (größe_1 >= 10 and größe_1 != 11) { grüß(\"wörld\"); } else { print nil; }\n";
+
+const RUNS: u32 = 5;
+
+fn lex(source: &str) -> Duration {
+    let mut best = Duration::MAX;
+    for _ in 0..RUNS {
+        let mut lexer = Lexer::new(source.to_owned());
+        let start = Instant::now();
+        let (tokens, errors) = lexer.scan_tokens();
+        best = best.min(start.elapsed());
+        assert!(!errors.had_error());
+        assert!(!tokens.is_empty());
+    }
+    best
+}
+
+// doubling the input should roughly double the time, a quadratic lexer would quadruple it
+fn main() {
+    println!("{:>12} {:>12} {:>12}", "bytes", "time", "ns/byte");
+    for power in 0..8 {
+        let source = CHUNK.repeat(1000 << power);
+        let time = lex(&source);
+        println!(
+            "{:>12} {:>12.2?} {:>12.2}",
+            source.len(),
+            time,
+            time.as_nanos() as f64 / source.len() as f64
+        );
+    }
+}
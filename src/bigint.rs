@@ -0,0 +1,524 @@
+use std::{
+    cmp::Ordering,
+    fmt::{self, Display},
+    ops::{Add, Mul, Neg, Shl, Shr, Sub},
+};
+
+const LIMB_BITS: u32 = 32;
+
+// an integer of any size, Int arithmetic moves into it when an i64 overflows.
+// the magnitude is stored little endian in 32 bit limbs with no zero limbs on
+// the end, so zero is an empty magnitude and is never negative
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct BigInt {
+    negative: bool,
+    magnitude: Vec<u32>,
+}
+
+impl BigInt {
+    fn from_parts(negative: bool, mut magnitude: Vec<u32>) -> Self {
+        while magnitude.last() == Some(&0) {
+            magnitude.pop();
+        }
+        let negative = negative && !magnitude.is_empty();
+        BigInt {
+            negative,
+            magnitude,
+        }
+    }
+
+    /// Reads digits in the given radix, `None` when one of them is not a digit.
+    pub fn from_str_radix(digits: &str, radix: u32) -> Option<Self> {
+        let (negative, digits) = match digits.strip_prefix('-') {
+            Some(digits) => (true, digits),
+            None => (false, digits),
+        };
+        if digits.is_empty() {
+            return None;
+        }
+        let mut magnitude = Vec::new();
+        for character in digits.chars() {
+            let digit = character.to_digit(radix)?;
+            mul_add_small(&mut magnitude, radix, digit);
+        }
+        Some(BigInt::from_parts(negative, magnitude))
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.magnitude.is_empty()
+    }
+
+    pub fn is_negative(&self) -> bool {
+        self.negative
+    }
+
+    pub fn abs(&self) -> BigInt {
+        BigInt::from_parts(false, self.magnitude.clone())
+    }
+
+    pub fn to_i64(&self) -> Option<i64> {
+        if self.magnitude.len() > 2 {
+            return None;
+        }
+        let value = self
+            .magnitude
+            .iter()
+            .rev()
+            .fold(0u64, |value, &limb| (value << LIMB_BITS) | limb as u64);
+        match self.negative {
+            true if value == i64::MIN.unsigned_abs() => Some(i64::MIN),
+            true => i64::try_from(value).ok().map(|value| -value),
+            false => i64::try_from(value).ok(),
+        }
+    }
+
+    // how many bits the magnitude takes, zero for zero
+    pub fn bits(&self) -> u64 {
+        match self.magnitude.last() {
+            Some(last) => {
+                (self.magnitude.len() as u64 - 1) * LIMB_BITS as u64
+                    + (LIMB_BITS - last.leading_zeros()) as u64
+            }
+            None => 0,
+        }
+    }
+
+    pub fn to_f64(&self) -> f64 {
+        let value = self
+            .magnitude
+            .iter()
+            .rev()
+            .fold(0.0, |value, &limb| value * 4294967296.0 + limb as f64);
+        match self.negative {
+            true => -value,
+            false => value,
+        }
+    }
+
+    /// Division truncating towards zero, `None` when dividing by zero.
+    pub fn div_rem(&self, other: &BigInt) -> Option<(BigInt, BigInt)> {
+        if other.is_zero() {
+            return None;
+        }
+        let (quotient, remainder) = div_rem_magnitude(&self.magnitude, &other.magnitude);
+        Some((
+            BigInt::from_parts(self.negative != other.negative, quotient),
+            BigInt::from_parts(self.negative, remainder),
+        ))
+    }
+
+    /// Division rounding down, so the remainder takes the sign of the divisor.
+    pub fn div_rem_floor(&self, other: &BigInt) -> Option<(BigInt, BigInt)> {
+        let (quotient, remainder) = self.div_rem(other)?;
+        if !remainder.is_zero() && remainder.is_negative() != other.is_negative() {
+            return Some((&quotient - &BigInt::from(1), &remainder + other));
+        }
+        Some((quotient, remainder))
+    }
+
+    // by repeated squaring
+    pub fn pow(&self, mut exponent: u32) -> BigInt {
+        let mut base = self.clone();
+        let mut result = BigInt::from(1);
+        while exponent > 0 {
+            if exponent & 1 == 1 {
+                result = &result * &base;
+            }
+            exponent >>= 1;
+            if exponent > 0 {
+                base = &base * &base;
+            }
+        }
+        result
+    }
+
+    pub fn gcd(&self, other: &BigInt) -> BigInt {
+        let (mut a, mut b) = (self.abs(), other.abs());
+        while !b.is_zero() {
+            let (_, remainder) = a.div_rem(&b).unwrap();
+            a = b;
+            b = remainder;
+        }
+        a
+    }
+}
+
+impl From<i64> for BigInt {
+    fn from(value: i64) -> Self {
+        let magnitude = value.unsigned_abs();
+        BigInt::from_parts(
+            value < 0,
+            vec![magnitude as u32, (magnitude >> LIMB_BITS) as u32],
+        )
+    }
+}
+
+impl Ord for BigInt {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self.negative, other.negative) {
+            (false, true) => Ordering::Greater,
+            (true, false) => Ordering::Less,
+            (false, false) => cmp_magnitude(&self.magnitude, &other.magnitude),
+            (true, true) => cmp_magnitude(&other.magnitude, &self.magnitude),
+        }
+    }
+}
+
+impl PartialOrd for BigInt {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Neg for &BigInt {
+    type Output = BigInt;
+
+    fn neg(self) -> BigInt {
+        BigInt::from_parts(!self.negative, self.magnitude.clone())
+    }
+}
+
+impl Add for &BigInt {
+    type Output = BigInt;
+
+    fn add(self, other: &BigInt) -> BigInt {
+        if self.negative == other.negative {
+            return BigInt::from_parts(
+                self.negative,
+                add_magnitude(&self.magnitude, &other.magnitude),
+            );
+        }
+        // different signs, take the smaller magnitude from the larger one
+        match cmp_magnitude(&self.magnitude, &other.magnitude) {
+            Ordering::Less => BigInt::from_parts(
+                other.negative,
+                sub_magnitude(&other.magnitude, &self.magnitude),
+            ),
+            _ => BigInt::from_parts(
+                self.negative,
+                sub_magnitude(&self.magnitude, &other.magnitude),
+            ),
+        }
+    }
+}
+
+impl Sub for &BigInt {
+    type Output = BigInt;
+
+    fn sub(self, other: &BigInt) -> BigInt {
+        self + &-other
+    }
+}
+
+impl Mul for &BigInt {
+    type Output = BigInt;
+
+    fn mul(self, other: &BigInt) -> BigInt {
+        let mut product = vec![0u32; self.magnitude.len() + other.magnitude.len()];
+        for (i, &a) in self.magnitude.iter().enumerate() {
+            let mut carry = 0u64;
+            for (j, &b) in other.magnitude.iter().enumerate() {
+                let sum = product[i + j] as u64 + a as u64 * b as u64 + carry;
+                product[i + j] = sum as u32;
+                carry = sum >> LIMB_BITS;
+            }
+            product[i + other.magnitude.len()] = carry as u32;
+        }
+        BigInt::from_parts(self.negative != other.negative, product)
+    }
+}
+
+// multiplies by a power of two, moving whole limbs then the bits left over
+impl Shl<u32> for &BigInt {
+    type Output = BigInt;
+
+    fn shl(self, amount: u32) -> BigInt {
+        if self.is_zero() {
+            return BigInt::default();
+        }
+        let (limbs, bits) = ((amount / LIMB_BITS) as usize, amount % LIMB_BITS);
+        let mut magnitude = vec![0u32; limbs];
+        magnitude.reserve(self.magnitude.len() + 1);
+        let mut carry = 0u32;
+        for &limb in &self.magnitude {
+            magnitude.push((limb << bits) | carry);
+            carry = match bits {
+                0 => 0,
+                _ => limb >> (LIMB_BITS - bits),
+            };
+        }
+        magnitude.push(carry);
+        BigInt::from_parts(self.negative, magnitude)
+    }
+}
+
+// divides by a power of two rounding down, like `div_rem_floor`
+impl Shr<u32> for &BigInt {
+    type Output = BigInt;
+
+    fn shr(self, amount: u32) -> BigInt {
+        let (limbs, bits) = ((amount / LIMB_BITS) as usize, amount % LIMB_BITS);
+        if limbs >= self.magnitude.len() {
+            return match self.negative {
+                true => BigInt::from(-1),
+                false => BigInt::default(),
+            };
+        }
+        // a negative value that loses any set bit rounds down, away from zero
+        let mut inexact = self.magnitude[..limbs].iter().any(|&limb| limb != 0);
+        let rest = &self.magnitude[limbs..];
+        inexact |= bits != 0 && rest[0] << (LIMB_BITS - bits) != 0;
+        let mut magnitude = Vec::with_capacity(rest.len());
+        for (i, &limb) in rest.iter().enumerate() {
+            let high = match bits {
+                0 => 0,
+                _ => rest
+                    .get(i + 1)
+                    .map_or(0, |&next| next << (LIMB_BITS - bits)),
+            };
+            magnitude.push((limb >> bits) | high);
+        }
+        let quotient = BigInt::from_parts(self.negative, magnitude);
+        match self.negative && inexact {
+            true => &quotient - &BigInt::from(1),
+            false => quotient,
+        }
+    }
+}
+
+// printed nine decimal digits at a time
+impl Display for BigInt {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_zero() {
+            return write!(f, "0");
+        }
+        let mut chunks = Vec::new();
+        let mut magnitude = self.magnitude.clone();
+        while !magnitude.is_empty() {
+            chunks.push(div_small(&mut magnitude, 1_000_000_000));
+        }
+        if self.negative {
+            write!(f, "-")?;
+        }
+        let mut chunks = chunks.iter().rev();
+        if let Some(first) = chunks.next() {
+            write!(f, "{first}")?;
+        }
+        for chunk in chunks {
+            write!(f, "{chunk:09}")?;
+        }
+        Ok(())
+    }
+}
+
+fn cmp_magnitude(a: &[u32], b: &[u32]) -> Ordering {
+    a.len()
+        .cmp(&b.len())
+        .then_with(|| a.iter().rev().cmp(b.iter().rev()))
+}
+
+fn add_magnitude(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let (long, short) = if a.len() >= b.len() { (a, b) } else { (b, a) };
+    let mut sum = Vec::with_capacity(long.len() + 1);
+    let mut carry = 0u64;
+    for (i, &limb) in long.iter().enumerate() {
+        let total = limb as u64 + *short.get(i).unwrap_or(&0) as u64 + carry;
+        sum.push(total as u32);
+        carry = total >> LIMB_BITS;
+    }
+    sum.push(carry as u32);
+    sum
+}
+
+// `a` must be at least as large as `b`
+fn sub_magnitude(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let mut difference = Vec::with_capacity(a.len());
+    let mut borrow = 0i64;
+    for (i, &limb) in a.iter().enumerate() {
+        let mut total = limb as i64 - *b.get(i).unwrap_or(&0) as i64 - borrow;
+        borrow = 0;
+        if total < 0 {
+            total += 1 << LIMB_BITS;
+            borrow = 1;
+        }
+        difference.push(total as u32);
+    }
+    difference
+}
+
+fn mul_add_small(magnitude: &mut Vec<u32>, factor: u32, addend: u32) {
+    let mut carry = addend as u64;
+    for limb in magnitude.iter_mut() {
+        let total = *limb as u64 * factor as u64 + carry;
+        *limb = total as u32;
+        carry = total >> LIMB_BITS;
+    }
+    if carry != 0 {
+        magnitude.push(carry as u32);
+    }
+}
+
+// divides in place and hands back the remainder
+fn div_small(magnitude: &mut Vec<u32>, divisor: u32) -> u32 {
+    let mut remainder = 0u64;
+    for limb in magnitude.iter_mut().rev() {
+        let total = (remainder << LIMB_BITS) | *limb as u64;
+        *limb = (total / divisor as u64) as u32;
+        remainder = total % divisor as u64;
+    }
+    while magnitude.last() == Some(&0) {
+        magnitude.pop();
+    }
+    remainder as u32
+}
+
+// shift and subtract one bit at a time, slow but only used for large divisors
+fn div_rem_magnitude(a: &[u32], b: &[u32]) -> (Vec<u32>, Vec<u32>) {
+    if let [divisor] = b {
+        let mut quotient = a.to_vec();
+        let remainder = div_small(&mut quotient, *divisor);
+        return (quotient, vec![remainder]);
+    }
+    let mut quotient = vec![0u32; a.len()];
+    let mut remainder: Vec<u32> = Vec::new();
+    for bit in (0..a.len() * LIMB_BITS as usize).rev() {
+        // remainder = remainder * 2 + next bit of `a`
+        let mut carry = (a[bit / 32] >> (bit % 32)) & 1;
+        for limb in remainder.iter_mut() {
+            let next = *limb >> (LIMB_BITS - 1);
+            *limb = (*limb << 1) | carry;
+            carry = next;
+        }
+        if carry != 0 {
+            remainder.push(carry);
+        }
+        if cmp_magnitude(&remainder, b) != Ordering::Less {
+            remainder = sub_magnitude(&remainder, b);
+            while remainder.last() == Some(&0) {
+                remainder.pop();
+            }
+            quotient[bit / 32] |= 1 << (bit % 32);
+        }
+    }
+    (quotient, remainder)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn big(digits: &str) -> BigInt {
+        BigInt::from_str_radix(digits, 10).unwrap()
+    }
+
+    fn div_rem(a: i64, b: i64) -> (i64, i64) {
+        let (quotient, remainder) = BigInt::from(a).div_rem(&BigInt::from(b)).unwrap();
+        (quotient.to_i64().unwrap(), remainder.to_i64().unwrap())
+    }
+
+    fn div_rem_floor(a: i64, b: i64) -> (i64, i64) {
+        let (quotient, remainder) = BigInt::from(a).div_rem_floor(&BigInt::from(b)).unwrap();
+        (quotient.to_i64().unwrap(), remainder.to_i64().unwrap())
+    }
+
+    #[test]
+    fn div_rem_truncates_towards_zero() {
+        assert_eq!(div_rem(7, 2), (3, 1));
+        assert_eq!(div_rem(7, -2), (-3, 1));
+        assert_eq!(div_rem(-7, 2), (-3, -1));
+        assert_eq!(div_rem(-7, -2), (3, -1));
+        assert_eq!(div_rem(-6, 2), (-3, 0));
+    }
+
+    #[test]
+    fn div_rem_floor_takes_the_sign_of_the_divisor() {
+        assert_eq!(div_rem_floor(7, 2), (3, 1));
+        assert_eq!(div_rem_floor(7, -2), (-4, -1));
+        assert_eq!(div_rem_floor(-7, 2), (-4, 1));
+        assert_eq!(div_rem_floor(-7, -2), (3, -1));
+        assert_eq!(div_rem_floor(-6, 2), (-3, 0));
+    }
+
+    #[test]
+    fn div_rem_of_multi_limb_values() {
+        let a = &BigInt::from(2).pow(100) + &BigInt::from(12345);
+        let b = &BigInt::from(2).pow(40) + &BigInt::from(3);
+        let (quotient, remainder) = a.div_rem(&b).unwrap();
+        assert_eq!(quotient, big("1152921504603701248"));
+        assert_eq!(remainder, big("9449529"));
+
+        let a = big("-1000000000000000000000000000007");
+        let b = big("1000000000000001");
+        let (quotient, remainder) = a.div_rem(&b).unwrap();
+        assert_eq!(quotient, big("-999999999999999"));
+        assert_eq!(remainder, big("-8"));
+        let (quotient, remainder) = a.div_rem_floor(&b).unwrap();
+        assert_eq!(quotient, big("-1000000000000000"));
+        assert_eq!(remainder, big("999999999999993"));
+    }
+
+    #[test]
+    fn dividing_by_zero_is_none() {
+        assert!(BigInt::from(1).div_rem(&BigInt::default()).is_none());
+        assert!(BigInt::from(1).div_rem_floor(&BigInt::default()).is_none());
+    }
+
+    #[test]
+    fn to_i64_at_the_boundaries() {
+        assert_eq!(BigInt::from(i64::MAX).to_i64(), Some(i64::MAX));
+        assert_eq!(BigInt::from(i64::MIN).to_i64(), Some(i64::MIN));
+        assert_eq!(big("9223372036854775808").to_i64(), None);
+        assert_eq!(big("-9223372036854775808").to_i64(), Some(i64::MIN));
+        assert_eq!(big("-9223372036854775809").to_i64(), None);
+        assert_eq!(BigInt::from(0).to_i64(), Some(0));
+    }
+
+    #[test]
+    fn display_pads_inner_chunks() {
+        assert_eq!(
+            big("10000000000000000001").to_string(),
+            "10000000000000000001"
+        );
+        assert_eq!(
+            BigInt::from(10).pow(27).to_string(),
+            "1000000000000000000000000000"
+        );
+        assert_eq!(BigInt::from(-1_000_000_000).to_string(), "-1000000000");
+        assert_eq!(BigInt::default().to_string(), "0");
+    }
+
+    #[test]
+    fn shifts_match_multiplying_and_dividing_by_powers_of_two() {
+        for value in [0, 1, -1, 5, -5, 12345, -12345, i64::MAX, i64::MIN] {
+            let value = BigInt::from(value);
+            for amount in [0, 1, 31, 32, 33, 64, 100] {
+                let power = BigInt::from(2).pow(amount);
+                assert_eq!(&value << amount, &value * &power);
+                let (quotient, _) = value.div_rem_floor(&power).unwrap();
+                assert_eq!(&value >> amount, quotient, "{value} >> {amount}");
+            }
+        }
+    }
+
+    #[test]
+    fn bits_counts_the_magnitude() {
+        assert_eq!(BigInt::from(0).bits(), 0);
+        assert_eq!(BigInt::from(1).bits(), 1);
+        assert_eq!(BigInt::from(-255).bits(), 8);
+        assert_eq!((&BigInt::from(1) << 100).bits(), 101);
+    }
+
+    #[test]
+    fn zero_is_never_negative() {
+        let zero = &BigInt::from(-5) + &BigInt::from(5);
+        assert!(!zero.is_negative());
+        assert_eq!(zero, BigInt::default());
+        assert_eq!(-&BigInt::default(), BigInt::default());
+    }
+
+    #[test]
+    fn gcd_ignores_signs() {
+        assert_eq!(BigInt::from(-12).gcd(&BigInt::from(18)), BigInt::from(6));
+        assert_eq!(BigInt::from(0).gcd(&BigInt::from(-7)), BigInt::from(7));
+    }
+}
@@ -0,0 +1,268 @@
+use std::{
+    cell::RefCell,
+    fmt::{self, Debug},
+    rc::Rc,
+    sync::OnceLock,
+    time::Instant,
+};
+
+use crate::{
+    environment::Environment,
+    error_handler::{RuntimeError, RuntimeErrorKind},
+    parser::{map_key, DataTypes, FunctionDeclaration, Interpret, Unwind},
+    tokens::{Token, TokenType},
+};
+
+// anything that can sit on the left of a call expression, `paren` is the
+// closing paren of the call for errors to point at
+pub trait Callable {
+    fn arity(&self) -> usize;
+    fn call(
+        &self,
+        interpreter: &mut Interpret,
+        paren: &Token,
+        arguments: Vec<DataTypes>,
+    ) -> Result<DataTypes, RuntimeError>;
+}
+
+// a user defined function together with the scope it was declared in
+pub struct Function {
+    declaration: Rc<FunctionDeclaration>,
+    closure: Rc<RefCell<Environment>>,
+    is_initializer: bool,
+}
+
+impl Function {
+    pub fn new(
+        declaration: Rc<FunctionDeclaration>,
+        closure: Rc<RefCell<Environment>>,
+        is_initializer: bool,
+    ) -> Self {
+        Function {
+            declaration,
+            closure,
+            is_initializer,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.declaration.name.text
+    }
+
+    // a method bound to an instance sees it as `this` in an extra scope
+    pub fn bind(&self, instance: DataTypes) -> Function {
+        let mut environment = Environment::new(Rc::clone(&self.closure));
+        environment.define("this".to_string(), Some(instance));
+        Function::new(
+            Rc::clone(&self.declaration),
+            Rc::new(RefCell::new(environment)),
+            self.is_initializer,
+        )
+    }
+
+    fn this(&self) -> Result<DataTypes, RuntimeError> {
+        let this = Token::new(
+            TokenType::THIS,
+            "this".to_string(),
+            None,
+            self.declaration.name.span,
+        );
+        self.closure.borrow().get(&this)
+    }
+}
+
+impl Callable for Function {
+    fn arity(&self) -> usize {
+        self.declaration.params.len()
+    }
+
+    fn call(
+        &self,
+        interpreter: &mut Interpret,
+        _paren: &Token,
+        arguments: Vec<DataTypes>,
+    ) -> Result<DataTypes, RuntimeError> {
+        let mut environment = Environment::new(Rc::clone(&self.closure));
+        for (param, argument) in self.declaration.params.iter().zip(arguments) {
+            environment.define(param.text.clone(), Some(argument));
+        }
+
+        match interpreter.execute_block(&self.declaration.body, environment) {
+            // an initializer always hands back the instance it set up
+            Ok(()) | Err(Unwind::Return(_)) if self.is_initializer => self.this(),
+            Ok(()) => Ok(DataTypes::Nil),
+            Err(Unwind::Return(value)) => Ok(value),
+            Err(Unwind::Error(error)) => Err(error),
+            // the parser only accepts `break` and `continue` inside a loop in the same function
+            Err(Unwind::Break(_) | Unwind::Continue(_)) => unreachable!(),
+        }
+    }
+}
+
+// the closure environment can refer back to the function itself
+impl Debug for Function {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "<fn {}>", self.name())
+    }
+}
+
+// a function implemented in rust and exposed to scripts as a global
+#[derive(Debug)]
+pub struct NativeFunction {
+    pub name: &'static str,
+    pub arity: usize,
+    pub function: fn(&Token, Vec<DataTypes>) -> Result<DataTypes, RuntimeError>,
+}
+
+impl Callable for NativeFunction {
+    fn arity(&self) -> usize {
+        self.arity
+    }
+
+    fn call(
+        &self,
+        _interpreter: &mut Interpret,
+        paren: &Token,
+        arguments: Vec<DataTypes>,
+    ) -> Result<DataTypes, RuntimeError> {
+        (self.function)(paren, arguments)
+    }
+}
+
+pub fn natives() -> Vec<NativeFunction> {
+    vec![
+        NativeFunction {
+            name: "clock",
+            arity: 0,
+            function: clock,
+        },
+        NativeFunction {
+            name: "len",
+            arity: 1,
+            function: len,
+        },
+        NativeFunction {
+            name: "push",
+            arity: 2,
+            function: push,
+        },
+        NativeFunction {
+            name: "pop",
+            arity: 1,
+            function: pop,
+        },
+        NativeFunction {
+            name: "has",
+            arity: 2,
+            function: has,
+        },
+        NativeFunction {
+            name: "keys",
+            arity: 1,
+            function: keys,
+        },
+        NativeFunction {
+            name: "values",
+            arity: 1,
+            function: values,
+        },
+    ]
+}
+
+// seconds since the first call
+fn clock(_paren: &Token, _arguments: Vec<DataTypes>) -> Result<DataTypes, RuntimeError> {
+    static START: OnceLock<Instant> = OnceLock::new();
+    let start = START.get_or_init(Instant::now);
+    Ok(DataTypes::Float(start.elapsed().as_secs_f64()))
+}
+
+// the number of elements in a List, entries in a Map or characters in a String
+fn len(paren: &Token, arguments: Vec<DataTypes>) -> Result<DataTypes, RuntimeError> {
+    match &arguments[0] {
+        DataTypes::List(list) => Ok(DataTypes::Int(list.borrow().len() as i64)),
+        DataTypes::Map(map) => Ok(DataTypes::Int(map.borrow().len() as i64)),
+        DataTypes::String(string) => Ok(DataTypes::Int(string.chars().count() as i64)),
+        _ => Err(RuntimeError::new(
+            paren,
+            RuntimeErrorKind::InvalidArgument("len", "a List, Map or String"),
+        )),
+    }
+}
+
+// adds to the end of the List in place
+fn push(paren: &Token, mut arguments: Vec<DataTypes>) -> Result<DataTypes, RuntimeError> {
+    let value = arguments.pop().unwrap();
+    match &arguments[0] {
+        DataTypes::List(list) => {
+            list.borrow_mut().push(value);
+            Ok(DataTypes::Nil)
+        }
+        _ => Err(RuntimeError::new(
+            paren,
+            RuntimeErrorKind::InvalidArgument("push", "a List"),
+        )),
+    }
+}
+
+// removes and returns the last element
+fn pop(paren: &Token, arguments: Vec<DataTypes>) -> Result<DataTypes, RuntimeError> {
+    match &arguments[0] {
+        DataTypes::List(list) => list
+            .borrow_mut()
+            .pop()
+            .ok_or_else(|| RuntimeError::new(paren, RuntimeErrorKind::EmptyList)),
+        _ => Err(RuntimeError::new(
+            paren,
+            RuntimeErrorKind::InvalidArgument("pop", "a List"),
+        )),
+    }
+}
+
+fn has(paren: &Token, arguments: Vec<DataTypes>) -> Result<DataTypes, RuntimeError> {
+    match &arguments[0] {
+        DataTypes::Map(map) => Ok(map
+            .borrow()
+            .contains_key(&map_key(paren, &arguments[1])?)
+            .into()),
+        _ => Err(RuntimeError::new(
+            paren,
+            RuntimeErrorKind::InvalidArgument("has", "a Map"),
+        )),
+    }
+}
+
+// a new List of the keys in insertion order
+fn keys(paren: &Token, arguments: Vec<DataTypes>) -> Result<DataTypes, RuntimeError> {
+    match &arguments[0] {
+        DataTypes::Map(map) => {
+            let keys = map
+                .borrow()
+                .iter()
+                .map(|(key, _)| key.clone().into())
+                .collect();
+            Ok(DataTypes::List(Rc::new(RefCell::new(keys))))
+        }
+        _ => Err(RuntimeError::new(
+            paren,
+            RuntimeErrorKind::InvalidArgument("keys", "a Map"),
+        )),
+    }
+}
+
+// a new List of the values in the same order as `keys`
+fn values(paren: &Token, arguments: Vec<DataTypes>) -> Result<DataTypes, RuntimeError> {
+    match &arguments[0] {
+        DataTypes::Map(map) => {
+            let values = map
+                .borrow()
+                .iter()
+                .map(|(_, value)| value.clone())
+                .collect();
+            Ok(DataTypes::List(Rc::new(RefCell::new(values))))
+        }
+        _ => Err(RuntimeError::new(
+            paren,
+            RuntimeErrorKind::InvalidArgument("values", "a Map"),
+        )),
+    }
+}
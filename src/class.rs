@@ -0,0 +1,108 @@
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    fmt::{self, Debug},
+    rc::Rc,
+};
+
+use crate::{
+    callable::{Callable, Function},
+    error_handler::{RuntimeError, RuntimeErrorKind},
+    parser::{DataTypes, Interpret},
+    tokens::Token,
+};
+
+#[derive(Debug)]
+pub struct Class {
+    pub name: String,
+    superclass: Option<Rc<Class>>,
+    methods: HashMap<String, Rc<Function>>,
+}
+
+impl Class {
+    pub fn new(
+        name: String,
+        superclass: Option<Rc<Class>>,
+        methods: HashMap<String, Rc<Function>>,
+    ) -> Self {
+        Class {
+            name,
+            superclass,
+            methods,
+        }
+    }
+
+    // methods are looked up on the class first and then up the superclass chain
+    pub fn find_method(&self, name: &str) -> Option<Rc<Function>> {
+        match self.methods.get(name) {
+            Some(method) => Some(Rc::clone(method)),
+            None => self
+                .superclass
+                .as_ref()
+                .and_then(|superclass| superclass.find_method(name)),
+        }
+    }
+}
+
+// calling a class creates an instance and runs its `init` method on it
+impl Callable for Rc<Class> {
+    fn arity(&self) -> usize {
+        self.find_method("init")
+            .map_or(0, |initializer| initializer.arity())
+    }
+
+    fn call(
+        &self,
+        interpreter: &mut Interpret,
+        paren: &Token,
+        arguments: Vec<DataTypes>,
+    ) -> Result<DataTypes, RuntimeError> {
+        let instance = DataTypes::Instance(Rc::new(RefCell::new(Instance::new(Rc::clone(self)))));
+        if let Some(initializer) = self.find_method("init") {
+            initializer
+                .bind(instance.clone())
+                .call(interpreter, paren, arguments)?;
+        }
+        Ok(instance)
+    }
+}
+
+pub struct Instance {
+    pub class: Rc<Class>,
+    fields: HashMap<String, DataTypes>,
+}
+
+impl Instance {
+    pub fn new(class: Rc<Class>) -> Self {
+        Instance {
+            class,
+            fields: HashMap::new(),
+        }
+    }
+
+    // fields shadow methods, methods come back bound to the instance
+    pub fn get(instance: &Rc<RefCell<Instance>>, name: &Token) -> Result<DataTypes, RuntimeError> {
+        if let Some(value) = instance.borrow().fields.get(&name.text) {
+            return Ok(value.clone());
+        }
+
+        let method = instance.borrow().class.find_method(&name.text);
+        match method {
+            Some(method) => Ok(DataTypes::Function(Rc::new(
+                method.bind(DataTypes::Instance(Rc::clone(instance))),
+            ))),
+            None => Err(RuntimeError::new(name, RuntimeErrorKind::UndefinedProperty)),
+        }
+    }
+
+    pub fn set(&mut self, name: &Token, value: DataTypes) {
+        self.fields.insert(name.text.clone(), value);
+    }
+}
+
+// fields can hold the instance itself
+impl Debug for Instance {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "<{} instance>", self.class.name)
+    }
+}
@@ -0,0 +1,52 @@
+use std::{cell::RefCell, collections::HashMap, rc::Rc};
+
+use crate::{
+    error_handler::{RuntimeError, RuntimeErrorKind},
+    parser::DataTypes,
+    tokens::Token,
+};
+
+// a variable declared with `let x;` is stored as None until it is assigned
+#[derive(Debug, Default)]
+pub struct Environment {
+    values: HashMap<String, Option<DataTypes>>,
+    enclosing: Option<Rc<RefCell<Environment>>>,
+}
+
+impl Environment {
+    pub fn new(enclosing: Rc<RefCell<Environment>>) -> Self {
+        Environment {
+            values: HashMap::new(),
+            enclosing: Some(enclosing),
+        }
+    }
+
+    pub fn define(&mut self, name: String, value: Option<DataTypes>) {
+        self.values.insert(name, value);
+    }
+
+    pub fn get(&self, name: &Token) -> Result<DataTypes, RuntimeError> {
+        match self.values.get(&name.text) {
+            Some(Some(value)) => Ok(value.clone()),
+            Some(None) => Err(RuntimeError::new(
+                name,
+                RuntimeErrorKind::UninitialisedVariable,
+            )),
+            None => match &self.enclosing {
+                Some(enclosing) => enclosing.borrow().get(name),
+                None => Err(RuntimeError::new(name, RuntimeErrorKind::UndefinedVariable)),
+            },
+        }
+    }
+
+    pub fn assign(&mut self, name: &Token, value: DataTypes) -> Result<(), RuntimeError> {
+        if let Some(slot) = self.values.get_mut(&name.text) {
+            *slot = Some(value);
+            return Ok(());
+        }
+        match &self.enclosing {
+            Some(enclosing) => enclosing.borrow_mut().assign(name, value),
+            None => Err(RuntimeError::new(name, RuntimeErrorKind::UndefinedVariable)),
+        }
+    }
+}
@@ -1,80 +1,602 @@
-use std::{error::Error, fmt::Display, fs::write, num::ParseFloatError};
-
-//TODO: add more types
-// enum ErrorType {
-//     Error,
-//     Warning,
-// }
-// #[derive(Default)]
-// struct Error {
-//     line: usize,
-//     place: String,
-//     message: String,
-// }
-
-// impl Error {
-//     fn report(&self) {
-//         println!(
-//             "[Line {}] Error {}: {}",
-//             self.line, self.place, self.message
-//         );
-//     }
-// }
-// #[derive(Default)]
-// pub struct ErrorSet {
-//     error_list: Vec<Error>,
-//     had_error: bool,
-// }
-
-// impl ErrorSet {
-//     pub fn error(&mut self, line: usize, message: String) {
-//         let error = Error {
-//             line,
-//             place: String::new(),
-//             message,
-//         };
-//         error.report();
-//         self.error_list.push(error);
-//         self.had_error = true;
-//     }
-
-//     pub fn error_where(&mut self, line: usize, place: String, message: String) {
-//         let error = Error {
-//             line,
-//             place,
-//             message,
-//         };
-//         error.report();
-//         self.error_list.push(error);
-//         self.had_error = true;
-//     }
-// }
+use std::{
+    error::Error,
+    fmt::{self, Display},
+};
+
+use crate::{
+    numeric::MAX_BITS,
+    tokens::{Span, Token, TokenType},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+impl Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Severity::Error => write!(f, "error"),
+            Severity::Warning => write!(f, "warning"),
+        }
+    }
+}
+
+// extra context pointing at another part of the source
+#[derive(Debug, Clone)]
+pub struct Label {
+    pub span: Span,
+    pub message: String,
+}
+
+/// A report about the source ready to be shown to the user: what went wrong,
+/// where, and optionally related places and hints on how to fix it.
+#[derive(Debug, Clone)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub message: String,
+    pub span: Span,
+    pub label: Option<String>,
+    pub secondary: Vec<Label>,
+    pub hints: Vec<String>,
+}
+
+impl Diagnostic {
+    pub fn error(message: String, span: Span) -> Self {
+        Diagnostic {
+            severity: Severity::Error,
+            message,
+            span,
+            label: None,
+            secondary: Vec::new(),
+            hints: Vec::new(),
+        }
+    }
+
+    pub fn with_label(mut self, label: impl Into<String>) -> Self {
+        self.label = Some(label.into());
+        self
+    }
+
+    pub fn with_secondary(mut self, span: Span, message: impl Into<String>) -> Self {
+        self.secondary.push(Label {
+            span,
+            message: message.into(),
+        });
+        self
+    }
+
+    pub fn with_hint(mut self, hint: impl Into<String>) -> Self {
+        self.hints.push(hint.into());
+        self
+    }
+
+    /// Renders the diagnostic with an excerpt of `source` (the same text the
+    /// spans were taken from), underlining the primary span with `^` and
+    /// secondary labels with `-`.
+    pub fn render(&self, source: &str) -> String {
+        let lines: Vec<&str> = source.lines().collect();
+
+        let mut marks: Vec<(Span, char, &str)> =
+            vec![(self.span, '^', self.label.as_deref().unwrap_or_default())];
+        for label in &self.secondary {
+            marks.push((label.span, '-', &label.message));
+        }
+        marks.sort_by_key(|(span, ..)| (span.start_line, span.start_column));
+
+        let width = marks
+            .iter()
+            .map(|(span, ..)| span.start_line.to_string().len())
+            .max()
+            .unwrap_or(1);
+        let pad = " ".repeat(width);
+
+        let mut out = format!("{}: {}\n", self.severity, self.message);
+        out.push_str(&format!(
+            "{pad}--> line {}:{}\n",
+            self.span.start_line, self.span.start_column
+        ));
+        out.push_str(&format!("{pad} |\n"));
+
+        let mut previous_line = None;
+        for (span, underline, message) in &marks {
+            let text = lines
+                .get(span.start_line.saturating_sub(1))
+                .copied()
+                .unwrap_or_default();
+            if previous_line != Some(span.start_line) {
+                out.push_str(&format!("{:>width$} | {}\n", span.start_line, text));
+                previous_line = Some(span.start_line);
+            }
+
+            // keep tabs so the underline lines up with the excerpt
+            let indent: String = text
+                .chars()
+                .take(span.start_column.saturating_sub(1))
+                .map(|char| if char == '\t' { '\t' } else { ' ' })
+                .collect();
+            let length = if span.end_line == span.start_line {
+                span.end_column.saturating_sub(span.start_column)
+            } else {
+                (text.chars().count() + 1).saturating_sub(span.start_column)
+            };
+            let marker = underline.to_string().repeat(length.max(1));
+            out.push_str(format!("{pad} | {indent}{marker} {message}").trim_end());
+            out.push('\n');
+        }
+
+        for hint in &self.hints {
+            out.push_str(&format!("{pad} = help: {hint}\n"));
+        }
+        out
+    }
+}
+
+// how a token shows up in a message
+fn describe(token: &Token) -> String {
+    match token.token_type {
+        TokenType::EOF => "end of file".to_string(),
+        _ => format!("`{}`", token.text),
+    }
+}
+
+// every error found in one pass, so they can all be reported together
+#[derive(Debug, Default)]
+pub struct ErrorSet {
+    error_list: Vec<Errors>,
+}
+
+impl ErrorSet {
+    pub fn push(&mut self, error: Errors) {
+        self.error_list.push(error);
+    }
+
+    pub fn append(&mut self, other: ErrorSet) {
+        self.error_list.extend(other.error_list);
+    }
+
+    pub fn had_error(&self) -> bool {
+        !self.error_list.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.error_list.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.error_list.is_empty()
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, Errors> {
+        self.error_list.iter()
+    }
+
+    pub fn diagnostics(&self) -> Vec<Diagnostic> {
+        self.error_list.iter().map(Errors::diagnostic).collect()
+    }
+
+    // in source order, whichever stage found them
+    pub fn render(&self, source: &str) -> String {
+        let mut errors: Vec<&Errors> = self.error_list.iter().collect();
+        errors.sort_by_key(|error| error.span().start);
+        errors
+            .into_iter()
+            .map(|error| error.diagnostic().render(source))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+impl IntoIterator for ErrorSet {
+    type Item = Errors;
+    type IntoIter = std::vec::IntoIter<Errors>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.error_list.into_iter()
+    }
+}
+
+// errors found while reading the source, before anything runs.
+// the boxed token is the one found where something else was expected, spans
+// mark where an unclosed bracket was opened
 #[derive(Debug)]
 pub enum Errors {
-    //add line data
-    UntermitedGroup,
-    UntermitedString,
-    UnexpectedChar,
-    NonPrimaryToken,
-    ParseFloatError(ParseFloatError),
+    UntermitedGroup(Box<Token>, Span),
+    UntermitedInterpolation(Box<Token>, Span),
+    UntermitedString(Span),
+    InvalidEscape(char, Span),
+    InvalidUnicodeEscape(Span),
+    UnexpectedChar(char, Span),
+    NonPrimaryToken(Box<Token>),
+    MissingSemicolon(Box<Token>),
+    UntermitedBlock(Box<Token>, Span),
+    MissingVariableName(Box<Token>),
+    InvalidAssignmentTarget(Span),
+    MissingCondition(Box<Token>),
+    UntermitedCondition(Box<Token>, Span),
+    MissingFunctionName(Box<Token>),
+    MissingParameterList(Box<Token>),
+    MissingParameterName(Box<Token>),
+    UntermitedParameterList(Box<Token>, Span),
+    MissingFunctionBody(Box<Token>),
+    UntermitedArgumentList(Box<Token>, Span),
+    UntermitedList(Box<Token>, Span),
+    UntermitedIndex(Box<Token>, Span),
+    UntermitedMap(Box<Token>, Span),
+    MissingMapColon(Box<Token>),
+    MissingTernaryElse(Box<Token>, Span),
+    ReturnOutsideFunction(Box<Token>),
+    JumpOutsideLoop(Box<Token>),
+    UndefinedLabel(Box<Token>),
+    MissingLabelColon(Box<Token>),
+    LabelWithoutLoop(Box<Token>),
+    MissingClassName(Box<Token>),
+    MissingSuperclassName(Box<Token>),
+    MissingClassBody(Box<Token>),
+    MissingPropertyName(Box<Token>),
+    MissingSuperMethod(Box<Token>),
+    InheritFromSelf(Box<Token>),
+    ReturnFromInitializer(Box<Token>),
+    ThisOutsideClass(Box<Token>),
+    SuperOutsideClass(Box<Token>),
+    SuperWithoutSuperclass(Box<Token>),
+    MissingDigits(&'static str, Span),
+    InvalidDigit(char, u32, Span),
+    TrailingSeparator(Span),
+    NumberTooLarge(Span),
+}
+
+impl Errors {
+    /// The token the parser found where it expected something else.
+    pub fn token(&self) -> Option<&Token> {
+        match self {
+            Errors::UntermitedString(_)
+            | Errors::InvalidEscape(..)
+            | Errors::InvalidUnicodeEscape(_)
+            | Errors::UnexpectedChar(..)
+            | Errors::InvalidAssignmentTarget(_)
+            | Errors::MissingDigits(..)
+            | Errors::InvalidDigit(..)
+            | Errors::TrailingSeparator(_)
+            | Errors::NumberTooLarge(_) => None,
+            Errors::UntermitedGroup(token, _)
+            | Errors::UntermitedInterpolation(token, _)
+            | Errors::NonPrimaryToken(token)
+            | Errors::MissingSemicolon(token)
+            | Errors::UntermitedBlock(token, _)
+            | Errors::MissingVariableName(token)
+            | Errors::MissingCondition(token)
+            | Errors::UntermitedCondition(token, _)
+            | Errors::MissingFunctionName(token)
+            | Errors::MissingParameterList(token)
+            | Errors::MissingParameterName(token)
+            | Errors::UntermitedParameterList(token, _)
+            | Errors::MissingFunctionBody(token)
+            | Errors::UntermitedArgumentList(token, _)
+            | Errors::UntermitedList(token, _)
+            | Errors::UntermitedIndex(token, _)
+            | Errors::UntermitedMap(token, _)
+            | Errors::MissingMapColon(token)
+            | Errors::MissingTernaryElse(token, _)
+            | Errors::ReturnOutsideFunction(token)
+            | Errors::JumpOutsideLoop(token)
+            | Errors::UndefinedLabel(token)
+            | Errors::MissingLabelColon(token)
+            | Errors::LabelWithoutLoop(token)
+            | Errors::MissingClassName(token)
+            | Errors::MissingSuperclassName(token)
+            | Errors::MissingClassBody(token)
+            | Errors::MissingPropertyName(token)
+            | Errors::MissingSuperMethod(token)
+            | Errors::InheritFromSelf(token)
+            | Errors::ReturnFromInitializer(token)
+            | Errors::ThisOutsideClass(token)
+            | Errors::SuperOutsideClass(token)
+            | Errors::SuperWithoutSuperclass(token) => Some(token),
+        }
+    }
+
+    /// Where in the source the error was found.
+    pub fn span(&self) -> Span {
+        match self {
+            Errors::UntermitedString(span)
+            | Errors::InvalidEscape(_, span)
+            | Errors::InvalidUnicodeEscape(span)
+            | Errors::UnexpectedChar(_, span)
+            | Errors::InvalidAssignmentTarget(span)
+            | Errors::MissingDigits(_, span)
+            | Errors::InvalidDigit(_, _, span)
+            | Errors::TrailingSeparator(span)
+            | Errors::NumberTooLarge(span) => *span,
+            _ => self.token().map_or(Span::default(), |token| token.span),
+        }
+    }
+
+    pub fn diagnostic(&self) -> Diagnostic {
+        let diagnostic = Diagnostic::error(self.to_string(), self.span());
+        match self {
+            Errors::UntermitedGroup(found, open)
+            | Errors::UntermitedInterpolation(found, open)
+            | Errors::UntermitedBlock(found, open)
+            | Errors::UntermitedCondition(found, open)
+            | Errors::UntermitedParameterList(found, open)
+            | Errors::UntermitedArgumentList(found, open)
+            | Errors::UntermitedList(found, open)
+            | Errors::UntermitedIndex(found, open)
+            | Errors::UntermitedMap(found, open) => diagnostic
+                .with_label(format!("found {}", describe(found)))
+                .with_secondary(*open, "opened here"),
+            Errors::MissingTernaryElse(found, question) => diagnostic
+                .with_label(format!("found {}", describe(found)))
+                .with_secondary(*question, "for this `?`"),
+            Errors::InvalidEscape(..) => diagnostic.with_label("unknown escape").with_hint(
+                "valid escapes are \\n \\t \\\\ \\\" \\$ and \\u{...}, or use a raw String r\"...\"",
+            ),
+            Errors::InvalidUnicodeEscape(_) => diagnostic
+                .with_label("not a Unicode character")
+                .with_hint("write the code point in hex between braces, like \\u{1F600}"),
+            Errors::UntermitedString(_) => diagnostic
+                .with_label("the String starts here")
+                .with_hint("add a closing (\") to end the String"),
+            Errors::UnexpectedChar(..) => diagnostic.with_label("not part of the language"),
+            Errors::NonPrimaryToken(found) => diagnostic
+                .with_label(format!("found {}", describe(found)))
+                .with_hint("expected a Number, String, Variable, Literal or (\"(\")"),
+            Errors::MissingSemicolon(found) => diagnostic
+                .with_label(format!("expected (\";\") before {}", describe(found)))
+                .with_hint("the previous Statment may be missing its (\";\")"),
+            Errors::InvalidAssignmentTarget(_) => diagnostic
+                .with_label("can not be assigned to")
+                .with_hint("only Variables, Properties and Indices can be assigned to"),
+            Errors::ReturnOutsideFunction(_) => diagnostic.with_label("not inside a Function"),
+            Errors::JumpOutsideLoop(_) => diagnostic.with_label("not inside a Loop"),
+            Errors::UndefinedLabel(_) => diagnostic
+                .with_label("unknown Label")
+                .with_hint("label a Loop by writing 'name: before its while or for"),
+            Errors::MissingLabelColon(found) | Errors::LabelWithoutLoop(found) => {
+                diagnostic.with_label(format!("found {}", describe(found)))
+            }
+            Errors::InheritFromSelf(_) => diagnostic.with_label("same name as the Class"),
+            Errors::ReturnFromInitializer(_) => diagnostic
+                .with_label("init always returns the new Instance")
+                .with_hint("use a bare (\"return;\") to leave init early"),
+            Errors::ThisOutsideClass(_) | Errors::SuperOutsideClass(_) => {
+                diagnostic.with_label("not inside a Method")
+            }
+            Errors::SuperWithoutSuperclass(_) => diagnostic
+                .with_label("this Class has no Superclass")
+                .with_hint("declare one with (\"class Name < Superclass\")"),
+            Errors::MissingDigits(..) => diagnostic
+                .with_label("not a valid Number")
+                .with_hint("write at least one digit, like 0xff or 1e-3"),
+            Errors::InvalidDigit(_, radix, _) => diagnostic
+                .with_label("not a valid Number")
+                .with_hint(match radix {
+                    16 => "hex Numbers use the digits 0-9 and a-f",
+                    8 => "octal Numbers use the digits 0-7",
+                    2 => "binary Numbers use the digits 0 and 1",
+                    _ => "put a space or operator between a Number and a name",
+                }),
+            Errors::TrailingSeparator(_) => diagnostic
+                .with_label("not a valid Number")
+                .with_hint("(\"_\") can only go between digits, like 1_000"),
+            Errors::NumberTooLarge(_) => diagnostic.with_label("too large to fit in a Number"),
+            Errors::MissingVariableName(found)
+            | Errors::MissingCondition(found)
+            | Errors::MissingFunctionName(found)
+            | Errors::MissingParameterList(found)
+            | Errors::MissingParameterName(found)
+            | Errors::MissingFunctionBody(found)
+            | Errors::MissingClassName(found)
+            | Errors::MissingSuperclassName(found)
+            | Errors::MissingClassBody(found)
+            | Errors::MissingPropertyName(found)
+            | Errors::MissingMapColon(found)
+            | Errors::MissingSuperMethod(found) => {
+                diagnostic.with_label(format!("found {}", describe(found)))
+            }
+        }
+    }
 }
 
 impl Display for Errors {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            Errors::UntermitedGroup => write!(f, "A Group Expression should close with (\")\")"),
-            Errors::UntermitedString => write!(f, "A String should close with (\") "),
-            Errors::UnexpectedChar => write!(f, "Unknown Character by the Lexer for found"),
-            Errors::NonPrimaryToken => write!(f, "Unknown Token for Primary Exprission"),
-            Errors::ParseFloatError(..) => write!(f, "NUMER Token Parse Error"),
+            Errors::UntermitedGroup(..) => {
+                write!(f, "A Group Expression should close with (\")\")")
+            }
+            Errors::UntermitedInterpolation(..) => {
+                write!(f, "An Interpolation should close with (\"}}\")")
+            }
+            Errors::UntermitedString(_) => write!(f, "A String should close with (\") "),
+            Errors::InvalidEscape(char, _) => write!(f, "Unknown Escape Sequence \\{char}"),
+            Errors::InvalidUnicodeEscape(_) => write!(f, "Invalid Unicode Escape"),
+            Errors::UnexpectedChar(char, _) => write!(f, "Unexpected Character {char:?}"),
+            Errors::NonPrimaryToken(_) => write!(f, "Unknown Token for Primary Exprission"),
+            Errors::MissingSemicolon(_) => write!(f, "A Statment should end with (\";\")"),
+            Errors::UntermitedBlock(..) => write!(f, "A Block should close with (\"}}\")"),
+            Errors::MissingVariableName(_) => write!(f, "Expected a Variable name after let"),
+            Errors::InvalidAssignmentTarget(_) => write!(f, "Invalid Assignment target"),
+            Errors::MissingCondition(_) => write!(f, "A Condition should start with (\"(\")"),
+            Errors::UntermitedCondition(..) => {
+                write!(f, "A Condition should close with (\")\")")
+            }
+            Errors::MissingFunctionName(_) => write!(f, "Expected a Function name after fun"),
+            Errors::MissingParameterList(_) => {
+                write!(f, "A Parameter list should start with (\"(\")")
+            }
+            Errors::MissingParameterName(_) => write!(f, "Expected a Parameter name"),
+            Errors::UntermitedParameterList(..) => {
+                write!(f, "A Parameter list should close with (\")\")")
+            }
+            Errors::MissingFunctionBody(_) => {
+                write!(f, "A Function body should start with (\"{{\")")
+            }
+            Errors::UntermitedArgumentList(..) => {
+                write!(f, "An Argument list should close with (\")\")")
+            }
+            Errors::UntermitedList(..) => write!(f, "A List should close with (\"]\")"),
+            Errors::UntermitedIndex(..) => write!(f, "An Index should close with (\"]\")"),
+            Errors::UntermitedMap(..) => write!(f, "A Map should close with (\"}}\")"),
+            Errors::MissingMapColon(_) => write!(f, "Expected (\":\") after a Map key"),
+            Errors::MissingTernaryElse(..) => {
+                write!(f, "A Conditional should have (\":\") between its branches")
+            }
+            Errors::ReturnOutsideFunction(_) => write!(f, "Can not return from outside a Function"),
+            Errors::JumpOutsideLoop(keyword) => {
+                write!(f, "Can not {} from outside a Loop", keyword.text)
+            }
+            Errors::UndefinedLabel(label) => {
+                write!(f, "No enclosing Loop is labelled {}", label.text)
+            }
+            Errors::MissingLabelColon(_) => write!(f, "Expected (\":\") after a Label"),
+            Errors::LabelWithoutLoop(_) => write!(f, "A Label should be followed by a Loop"),
+            Errors::MissingClassName(_) => write!(f, "Expected a Class name after class"),
+            Errors::MissingSuperclassName(_) => {
+                write!(f, "Expected a Superclass name after (\"<\")")
+            }
+            Errors::MissingClassBody(_) => write!(f, "A Class body should start with (\"{{\")"),
+            Errors::MissingPropertyName(_) => {
+                write!(f, "Expected a Property name after (\".\")")
+            }
+            Errors::MissingSuperMethod(_) => {
+                write!(f, "Expected (\".\") and a Method name after super")
+            }
+            Errors::InheritFromSelf(_) => write!(f, "A Class can not inherit from itself"),
+            Errors::ReturnFromInitializer(_) => {
+                write!(f, "Can not return a value from an Initializer")
+            }
+            Errors::ThisOutsideClass(_) => write!(f, "Can not use this outside of a Class"),
+            Errors::SuperOutsideClass(_) => write!(f, "Can not use super outside of a Class"),
+            Errors::SuperWithoutSuperclass(_) => {
+                write!(f, "Can not use super in a Class with no Superclass")
+            }
+            Errors::MissingDigits(place, _) => write!(f, "Expected digits {place}"),
+            Errors::InvalidDigit(char, radix, _) => {
+                write!(f, "Invalid digit {char:?} in a base {radix} Number")
+            }
+            Errors::TrailingSeparator(_) => write!(f, "A Number can not end with (\"_\")"),
+            Errors::NumberTooLarge(_) => write!(f, "Number literal is too large"),
         }
     }
 }
 
 impl Error for Errors {}
 
-impl From<ParseFloatError> for Errors {
-    fn from(value: ParseFloatError) -> Self {
-        Errors::ParseFloatError(value)
+// an error raised while a program is running, tied to the token that caused it
+#[derive(Debug)]
+pub struct RuntimeError {
+    pub token: Box<Token>,
+    pub kind: RuntimeErrorKind,
+}
+
+#[derive(Debug)]
+pub enum RuntimeErrorKind {
+    UndefinedVariable,
+    UninitialisedVariable,
+    NotCallable,
+    ArityMismatch(usize, usize),
+    SuperclassNotClass,
+    NotAnInstance,
+    UndefinedProperty,
+    InvalidOperand(&'static str),
+    InvalidOperands(&'static str),
+    UnsupportedOperator,
+    UnsupportedLiteral,
+    DivisionByZero,
+    StackOverflow,
+    NumberTooLarge,
+    InvalidShift,
+    InvalidSyntax,
+    NotIndexable,
+    NotSliceable,
+    UnhashableKey,
+    MissingKey(String),
+    InvalidIndex,
+    IndexOutOfBounds(i64, usize),
+    EmptyList,
+    InvalidArgument(&'static str, &'static str),
+}
+
+impl RuntimeError {
+    pub fn new(token: &Token, kind: RuntimeErrorKind) -> Self {
+        RuntimeError {
+            token: Box::new(token.clone()),
+            kind,
+        }
+    }
+}
+
+impl RuntimeError {
+    fn message(&self) -> String {
+        let token = &self.token.text;
+        match &self.kind {
+            RuntimeErrorKind::UndefinedVariable => format!("Undefined Variable {token}"),
+            RuntimeErrorKind::UninitialisedVariable => {
+                format!("Variable {token} is used before it is assigned")
+            }
+            RuntimeErrorKind::NotCallable => "Only Functions and Classes can be called".to_string(),
+            RuntimeErrorKind::ArityMismatch(expected, found) => {
+                format!("Expected {expected} Arguments but got {found}")
+            }
+            RuntimeErrorKind::SuperclassNotClass => {
+                format!("Superclass of {token} must be a Class")
+            }
+            RuntimeErrorKind::NotAnInstance => {
+                format!("Only Instances have properties, can not access {token}")
+            }
+            RuntimeErrorKind::UndefinedProperty => format!("Undefined Property {token}"),
+            RuntimeErrorKind::InvalidOperand(expected) => {
+                format!("Operand of {token} must be {expected}")
+            }
+            RuntimeErrorKind::InvalidOperands(expected) => {
+                format!("Operands of {token} must be {expected}")
+            }
+            RuntimeErrorKind::UnsupportedOperator => format!("Unsupported Operator {token}"),
+            RuntimeErrorKind::UnsupportedLiteral => format!("Unsupported Literal {token}"),
+            RuntimeErrorKind::DivisionByZero => "Division by zero".to_string(),
+            RuntimeErrorKind::StackOverflow => "Stack overflow, too many nested calls".to_string(),
+            RuntimeErrorKind::NumberTooLarge => {
+                format!("Result of {token} would be larger than {MAX_BITS} bits")
+            }
+            RuntimeErrorKind::InvalidShift => {
+                format!("Right operand of {token} must be an Int from 0 to {MAX_BITS}")
+            }
+            RuntimeErrorKind::InvalidSyntax => {
+                format!("Can not run {token}, it failed to parse")
+            }
+            RuntimeErrorKind::NotIndexable => "Only Lists and Maps can be indexed".to_string(),
+            RuntimeErrorKind::NotSliceable => "Only Lists can be sliced".to_string(),
+            RuntimeErrorKind::UnhashableKey => {
+                "Map keys must be Strings, Ints, Bools or nil".to_string()
+            }
+            RuntimeErrorKind::MissingKey(key) => format!("Key {key} is not in the Map"),
+            RuntimeErrorKind::InvalidIndex => "List indices must be Ints".to_string(),
+            RuntimeErrorKind::IndexOutOfBounds(index, len) => {
+                format!("Index {index} is out of bounds for a List of length {len}")
+            }
+            RuntimeErrorKind::EmptyList => "Can not pop from an empty List".to_string(),
+            RuntimeErrorKind::InvalidArgument(function, expected) => {
+                format!("Argument of {function} must be {expected}")
+            }
+        }
+    }
+
+    pub fn diagnostic(&self) -> Diagnostic {
+        Diagnostic::error(self.message(), self.token.span)
     }
 }
+
+impl Display for RuntimeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "[Line {}] Runtime Error: {}",
+            self.token.line,
+            self.message()
+        )
+    }
+}
+
+impl Error for RuntimeError {}
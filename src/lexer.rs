@@ -1,43 +1,98 @@
-use std::{collections::TryReserveError, ops::Range};
-
 use crate::{
-    error_handler::Errors,
-    tokens::{LiteralType, Token, TokenType},
+    bigint::BigInt,
+    error_handler::{ErrorSet, Errors},
+    tokens::{LiteralType, Span, Token, TokenType},
 };
 
 pub struct Lexer {
     source: String,
-    //TODO: Tokens
-    pub token_list: Vec<Token>,
-    start: usize,
+    // the token scan_token just finished, handed out by `next`
+    token: Option<Token>,
+    done: bool,
+    // open braces inside each `${ }` being scanned, innermost last, with the
+    // first error in the String around it so far
+    interpolations: Vec<(usize, Option<Errors>)>,
+    // chars read so far, columns count chars rather than bytes
     current: usize,
     line: usize,
+    // byte offsets of the token start and of `current`, chars can be wider than a byte
+    start_byte: usize,
+    current_byte: usize,
+    // char index where the current line begins, used for columns
+    line_start: usize,
+    start_line: usize,
+    start_column: usize,
+}
+
+impl Iterator for Lexer {
+    type Item = Result<Token, Errors>;
+
+    // scans only as far as the next token, the last one is always EOF
+    fn next(&mut self) -> Option<Self::Item> {
+        while !self.done {
+            self.start_byte = self.current_byte;
+            self.start_line = self.line;
+            self.start_column = self.column();
+
+            if self.is_at_end() {
+                self.done = true;
+                self.add_token(TokenType::EOF);
+            } else if let Err(error) = self.scan_token() {
+                return Some(Err(error));
+            }
+            if let Some(token) = self.token.take() {
+                return Some(Ok(token));
+            }
+        }
+        None
+    }
 }
 
 impl Lexer {
     pub fn new(source: String) -> Self {
         Lexer {
             source,
-            token_list: Vec::new(),
-            start: 0,
+            token: None,
+            done: false,
+            interpolations: Vec::new(),
             current: 0,
             line: 1,
+            start_byte: 0,
+            current_byte: 0,
+            line_start: 0,
+            start_line: 1,
+            start_column: 1,
         }
     }
-    pub fn scan_tokens(&mut self) -> Result<Vec<Token>, Errors> {
-        while !self.is_at_end() {
-            self.start = self.current;
-            self.scan_token()?;
+    // every token up front, bad characters or literals become ERROR tokens
+    pub fn scan_tokens(&mut self) -> (Vec<Token>, ErrorSet) {
+        let mut error_set = ErrorSet::default();
+        let tokens = self.tokens(&mut error_set).collect();
+        (tokens, error_set)
+    }
+
+    // tokens one at a time, errors go into `error_set` and scanning carries on
+    pub fn tokens<'a>(&'a mut self, error_set: &'a mut ErrorSet) -> Tokens<'a> {
+        Tokens {
+            lexer: self,
+            error_set,
         }
+    }
 
-        self.token_list.push(Token::new(
-            TokenType::EOF,
-            String::default(),
-            None,
-            self.line,
-        ));
+    fn column(&self) -> usize {
+        self.current - self.line_start + 1
+    }
 
-        return Ok(self.token_list.clone());
+    // from the start of the current token up to the character about to be read
+    fn span(&self) -> Span {
+        Span {
+            start: self.start_byte,
+            end: self.current_byte,
+            start_line: self.start_line,
+            start_column: self.start_column,
+            end_line: self.line,
+            end_column: self.column(),
+        }
     }
 
     fn scan_token(&mut self) -> Result<(), Errors> {
@@ -45,14 +100,71 @@ impl Lexer {
         match character {
             '(' => self.add_token(TokenType::LeftPara),
             ')' => self.add_token(TokenType::RightPara),
-            '{' => self.add_token(TokenType::LeftBrace),
-            '}' => self.add_token(TokenType::RightBrace),
+            '{' => {
+                if let Some((depth, _)) = self.interpolations.last_mut() {
+                    *depth += 1;
+                }
+                self.add_token(TokenType::LeftBrace)
+            }
+            '}' => match self.interpolations.last_mut() {
+                // closes a `${`, the String carries on after it
+                Some((0, _)) => {
+                    let (_, error) = self.interpolations.pop().unwrap();
+                    self.string(false, error)?
+                }
+                Some((depth, _)) => {
+                    *depth -= 1;
+                    self.add_token(TokenType::RightBrace)
+                }
+                None => self.add_token(TokenType::RightBrace),
+            },
+            '[' => self.add_token(TokenType::LeftBracket),
+            ']' => self.add_token(TokenType::RightBracket),
             ',' => self.add_token(TokenType::COMMA),
             '.' => self.add_token(TokenType::DOT),
-            '-' => self.add_token(TokenType::MINUS),
-            '+' => self.add_token(TokenType::PLUS),
+            '-' => {
+                let token_type = if self.match_next('=') {
+                    TokenType::MinusEqual
+                } else {
+                    TokenType::MINUS
+                };
+                self.add_token(token_type);
+            }
+            '+' => {
+                let token_type = if self.match_next('=') {
+                    TokenType::PlusEqual
+                } else {
+                    TokenType::PLUS
+                };
+                self.add_token(token_type);
+            }
             ';' => self.add_token(TokenType::SEMICOLON),
-            '*' => self.add_token(TokenType::STAR),
+            '*' => {
+                let token_type = if self.match_next('*') {
+                    TokenType::StarStar
+                } else if self.match_next('=') {
+                    TokenType::StarEqual
+                } else {
+                    TokenType::STAR
+                };
+                self.add_token(token_type);
+            }
+            '%' => self.add_token(TokenType::PERCENT),
+            '&' => self.add_token(TokenType::AMPERSAND),
+            '|' => self.add_token(TokenType::PIPE),
+            '^' => self.add_token(TokenType::CARET),
+            '~' => self.add_token(TokenType::TILDE),
+            ':' => self.add_token(TokenType::COLON),
+            '?' => {
+                let token_type = if self.match_next('?') {
+                    TokenType::QuestionQuestion
+                } else if self.match_next('.') {
+                    TokenType::QuestionDot
+                } else {
+                    TokenType::QUESTION
+                };
+                self.add_token(token_type);
+            }
             '!' => {
                 let token_type = if self.match_next('=') {
                     TokenType::BangEqual
@@ -72,6 +184,8 @@ impl Lexer {
             '<' => {
                 let token_type = if self.match_next('=') {
                     TokenType::LessEqual
+                } else if self.match_next('<') {
+                    TokenType::LessLess
                 } else {
                     TokenType::LESS
                 };
@@ -80,45 +194,68 @@ impl Lexer {
             '>' => {
                 let token_type = if self.match_next('=') {
                     TokenType::GreaterEqual
+                } else if self.match_next('>') {
+                    TokenType::GreaterGreater
                 } else {
                     TokenType::GREATER
                 };
                 self.add_token(token_type);
             }
             '/' => {
-                if self.match_next('/') {
-                    while self.peek() != Some('\n') && !self.is_at_end() {
-                        self.advance();
-                    }
+                let token_type = if self.match_next('/') {
+                    TokenType::SlashSlash
+                } else if self.match_next('=') {
+                    TokenType::SlashEqual
                 } else {
-                    self.add_token(TokenType::SLASH)
+                    TokenType::SLASH
+                };
+                self.add_token(token_type);
+            }
+            // `//` is integer division, so comments start with `#`
+            '#' => {
+                while self.peek() != Some('\n') && !self.is_at_end() {
+                    self.advance();
                 }
             }
-            '\n' => self.line += 1,
+            '\n' => {}
             ' ' => {}
             '\r' => {}
             '\t' => {}
-            '"' => self.string()?,
-            char if char.is_digit(10) => self.number()?,
+            '"' => self.string(false, None)?,
+            // r"..." keeps every backslash as it is
+            'r' if self.peek() == Some('"') => {
+                self.advance();
+                self.string(true, None)?
+            }
+            '\'' if self
+                .peek()
+                .is_some_and(|char| char.is_alphabetic() || char == '_') =>
+            {
+                self.label()
+            }
+            char if char.is_ascii_digit() => self.number(char)?,
             char if char.is_alphabetic() || char == '_' => self.identifier(),
-            char => {
-                //error unexpeted char
-                // self.error_set
-                //     .error_where(self.line, char.to_string(), "Unexpeted char".to_owned())
-                return Err(Errors::UnexpectedChar);
+            _ => {
+                return Err(Errors::UnexpectedChar(character, self.span()));
             }
         }
         Ok(())
     }
 
     fn is_at_end(&self) -> bool {
-        self.current >= self.source.len()
+        self.current_byte >= self.source.len()
     }
 
+    // the source is only ever read forward from `current_byte`, so lexing stays linear
     fn advance(&mut self) -> char {
-        let character = self.source.chars().nth(self.current).unwrap();
+        let character = self.peek().unwrap();
 
         self.current += 1;
+        self.current_byte += character.len_utf8();
+        if character == '\n' {
+            self.line += 1;
+            self.line_start = self.current;
+        }
 
         character
     }
@@ -127,98 +264,252 @@ impl Lexer {
         if self.is_at_end() {
             return false;
         }
-        if self
-            .source
-            .chars()
-            .nth(self.current)
-            .is_some_and(|char| char != pattern)
-        {
-            return false;
+        if self.peek().is_some_and(|char| char != pattern) {
+            false
         } else {
             self.current += 1;
-            return true;
+            self.current_byte += pattern.len_utf8();
+            true
         }
     }
 
     fn add_token(&mut self, token_type: TokenType) {
-        return self.add_token_with_literal(token_type, None);
+        self.add_token_with_literal(token_type, None)
     }
 
     fn add_token_with_literal(&mut self, token_type: TokenType, literal: Option<LiteralType>) {
-        let sub_string = &self.source[self.start..self.current];
-        self.token_list.push(Token::new(
+        let sub_string = &self.source[self.start_byte..self.current_byte];
+        self.token = Some(Token::new(
             token_type,
             sub_string.to_string(),
             literal,
-            self.line,
+            self.span(),
         ))
     }
 
     fn peek(&self) -> Option<char> {
-        // if self.is_at_end() {
-        //     '\0'
-        // } else {
-        //     self.source.chars().nth(self.current).is_some()
-        // }
-
-        self.source.chars().nth(self.current)
+        self.source[self.current_byte..].chars().next()
     }
 
-    fn string(&mut self) -> Result<(), Errors> {
-        while self.peek() != Some('"') && !self.is_at_end() {
-            if self.peek() == Some('\n') {
-                self.line += 1;
+    // the literal holds the cooked value, the token text keeps the escapes as written.
+    // also picks a String back up after the `}` of an interpolation
+    // `error` is carried over from the part of the String before a `${ }`, so a
+    // bad escape is reported once the whole String is read
+    fn string(&mut self, raw: bool, mut error: Option<Errors>) -> Result<(), Errors> {
+        let mut value = String::new();
+        while let Some(character) = self.peek() {
+            if character == '"' {
+                break;
+            }
+            if character == '$' && self.peek_next() == Some('{') && !raw {
+                self.advance();
+                self.advance();
+                self.interpolations.push((0, error));
+                self.add_token_with_literal(
+                    TokenType::INTERPOLATION,
+                    Some(LiteralType::String(value)),
+                );
+                return Ok(());
+            }
+            if character == '\\' && !raw {
+                match self.escape() {
+                    Ok(Some(character)) => value.push(character),
+                    Ok(None) => {}
+                    // keep going to the closing quote so scanning resumes after the String
+                    Err(err) => {
+                        error.get_or_insert(err);
+                    }
+                }
+            } else {
+                value.push(self.advance());
             }
-            self.advance();
         }
         if self.is_at_end() {
-            return Err(Errors::UntermitedString);
+            return Err(Errors::UntermitedString(self.span()));
         }
         self.advance();
-        let string_value = &self.source[self.start + 1..self.current - 1];
-        self.add_token_with_literal(
-            TokenType::STRING,
-            Some(LiteralType::String(string_value.to_string())),
-        );
+        if let Some(error) = error {
+            return Err(error);
+        }
+        self.add_token_with_literal(TokenType::STRING, Some(LiteralType::String(value)));
         Ok(())
     }
 
-    fn number(&mut self) -> Result<(), Errors> {
-        let digits: Range<Option<char>> = Some('0')..Some('9');
+    // reads one escape starting at the backslash, None when the source ends inside it
+    fn escape(&mut self) -> Result<Option<char>, Errors> {
+        let (start_byte, start_line, start_column) = (self.current_byte, self.line, self.column());
+        self.advance();
+        let escape_span = |lexer: &Lexer| Span {
+            start: start_byte,
+            start_line,
+            start_column,
+            ..lexer.span()
+        };
 
-        while digits.contains(&self.peek()) {
-            self.advance();
+        let character = match self.peek() {
+            Some(character) => character,
+            None => return Ok(None),
+        };
+        self.advance();
+        match character {
+            'n' => Ok(Some('\n')),
+            't' => Ok(Some('\t')),
+            '\\' => Ok(Some('\\')),
+            '"' => Ok(Some('"')),
+            // `\${` is a literal `${` rather than an interpolation
+            '$' => Ok(Some('$')),
+            'u' => {
+                if !self.match_next('{') {
+                    return Err(Errors::InvalidUnicodeEscape(escape_span(self)));
+                }
+                let digits_start = self.current_byte;
+                while self.peek().is_some_and(|char| char.is_ascii_hexdigit()) {
+                    self.advance();
+                }
+                let digits = &self.source[digits_start..self.current_byte];
+                let code = match digits.len() {
+                    1..=6 => u32::from_str_radix(digits, 16)
+                        .ok()
+                        .and_then(char::from_u32),
+                    _ => None,
+                };
+                if !self.match_next('}') {
+                    return Err(Errors::InvalidUnicodeEscape(escape_span(self)));
+                }
+                code.map(Some)
+                    .ok_or_else(|| Errors::InvalidUnicodeEscape(escape_span(self)))
+            }
+            _ => Err(Errors::InvalidEscape(character, escape_span(self))),
         }
-        if self.peek() == Some('.') && digits.contains(&self.peek_next()) {
-            self.advance();
-            while digits.contains(&self.peek()) {
+    }
+
+    // 12, 1_000, 2.5, 1e-3, 0xff, 0b1010 and 0o17
+    fn number(&mut self, first: char) -> Result<(), Errors> {
+        let radix = match (first, self.peek()) {
+            ('0', Some('x' | 'X')) => 16,
+            ('0', Some('b' | 'B')) => 2,
+            ('0', Some('o' | 'O')) => 8,
+            _ => 10,
+        };
+
+        // a Number with no fraction or exponent stays an Integer
+        let value = if radix == 10 {
+            let mut is_float = false;
+            self.digits(10)?;
+            if self.peek() == Some('.') && self.peek_next().is_some_and(|c| c.is_ascii_digit()) {
+                is_float = true;
                 self.advance();
+                self.digits(10)?;
             }
-        }
-        self.add_token_with_literal(
-            TokenType::NUMBER,
-            Some(LiteralType::Number(
-                self.source[self.start..self.current].parse::<f32>()?,
-            )),
-        );
+            if matches!(self.peek(), Some('e' | 'E')) {
+                is_float = true;
+                self.advance();
+                if matches!(self.peek(), Some('+' | '-')) {
+                    self.advance();
+                }
+                if self.digits(10)? == 0 {
+                    return Err(self
+                        .malformed_number(|span| Errors::MissingDigits("in the exponent", span)));
+                }
+            }
+            self.check_number_end(10)?;
+            let text = self.source[self.start_byte..self.current_byte].replace('_', "");
+            match is_float {
+                // the text is already checked, so only an overflow to inf can go wrong
+                true => match text.parse::<f64>() {
+                    Ok(num) if num.is_finite() => LiteralType::Float(num),
+                    _ => return Err(Errors::NumberTooLarge(self.span())),
+                },
+                false => integer_literal(&text, 10),
+            }
+        } else {
+            self.advance();
+            let digits_start = self.current_byte;
+            if self.digits(radix)? == 0 {
+                let prefix = match radix {
+                    16 => "after 0x",
+                    2 => "after 0b",
+                    _ => "after 0o",
+                };
+                return Err(self.malformed_number(|span| Errors::MissingDigits(prefix, span)));
+            }
+            self.check_number_end(radix)?;
+            let digits = self.source[digits_start..self.current_byte].replace('_', "");
+            integer_literal(&digits, radix)
+        };
+        self.add_token_with_literal(TokenType::NUMBER, Some(value));
         Ok(())
     }
 
+    // digits of the radix with `_` allowed between them, returns how many digits there were
+    fn digits(&mut self, radix: u32) -> Result<usize, Errors> {
+        let mut count = 0;
+        let mut trailing_separator = false;
+        while let Some(character) = self.peek() {
+            if character == '_' {
+                trailing_separator = true;
+            } else if character.is_digit(radix) {
+                trailing_separator = false;
+                count += 1;
+            } else {
+                break;
+            }
+            self.advance();
+        }
+        if trailing_separator {
+            return Err(self.malformed_number(Errors::TrailingSeparator));
+        }
+        Ok(count)
+    }
+
+    // a Number running straight into letters or digits of the wrong base is one bad token
+    fn check_number_end(&mut self, radix: u32) -> Result<(), Errors> {
+        match self.peek() {
+            Some(character) if character.is_alphanumeric() || character == '_' => {
+                Err(self.malformed_number(|span| Errors::InvalidDigit(character, radix, span)))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    // skips the rest of a broken Number so scanning resumes after it
+    fn malformed_number(&mut self, error: impl FnOnce(Span) -> Errors) -> Errors {
+        while self
+            .peek()
+            .is_some_and(|character| character.is_alphanumeric() || character == '_')
+        {
+            self.advance();
+        }
+        error(self.span())
+    }
+
     fn peek_next(&self) -> Option<char> {
-        // if self.current + 1 >= self.source.len() {
-        //     return None;
-        // }
-        self.source.chars().nth(self.current + 1)
+        self.source[self.current_byte..].chars().nth(1)
+    }
+
+    fn label(&mut self) {
+        while self
+            .peek()
+            .is_some_and(|char| char.is_alphanumeric() || char == '_')
+        {
+            self.advance();
+        }
+        self.add_token(TokenType::LABEL);
     }
 
     fn identifier(&mut self) {
-        while self.peek().is_some_and(|char| char.is_alphanumeric()) {
+        while self
+            .peek()
+            .is_some_and(|char| char.is_alphanumeric() || char == '_')
+        {
             self.advance();
         }
-        let text = &self.source[self.start..self.current];
+        let text = &self.source[self.start_byte..self.current_byte];
         let token_type = match text {
             "and" => TokenType::AND,
+            "break" => TokenType::BREAK,
             "class" => TokenType::CLASS,
+            "continue" => TokenType::CONTINUE,
             "else" => TokenType::ELSE,
             "false" => TokenType::FALSE,
             "fun" => TokenType::FUN,
@@ -228,6 +519,7 @@ impl Lexer {
             "or" => TokenType::OR,
             "print" => TokenType::PRINT,
             "return" => TokenType::RETURN,
+            "super" => TokenType::SUPER,
             "this" => TokenType::THIS,
             "true" => TokenType::TRUE,
             "let" => TokenType::LET,
@@ -237,3 +529,132 @@ impl Lexer {
         self.add_token(token_type);
     }
 }
+
+// the digits are already checked, anything too large for an i64 becomes a BigInt
+fn integer_literal(digits: &str, radix: u32) -> LiteralType {
+    match i64::from_str_radix(digits, radix) {
+        Ok(num) => LiteralType::Integer(num),
+        Err(_) => LiteralType::BigInteger(BigInt::from_str_radix(digits, radix).unwrap()),
+    }
+}
+
+// a lexer that reports its errors on the side and hands out ERROR tokens in their place
+pub struct Tokens<'a> {
+    lexer: &'a mut Lexer,
+    error_set: &'a mut ErrorSet,
+}
+
+impl Iterator for Tokens<'_> {
+    type Item = Token;
+
+    fn next(&mut self) -> Option<Token> {
+        match self.lexer.next()? {
+            Ok(token) => Some(token),
+            Err(error) => {
+                self.error_set.push(error);
+                // the bad text is still the current token
+                self.lexer.add_token(TokenType::ERROR);
+                self.lexer.token.take()
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scan(source: &str) -> (Vec<Token>, ErrorSet) {
+        Lexer::new(source.to_owned()).scan_tokens()
+    }
+
+    fn types(tokens: &[Token]) -> Vec<TokenType> {
+        tokens
+            .iter()
+            .map(|token| token.token_type.clone())
+            .collect()
+    }
+
+    fn text(token: &Token) -> String {
+        token
+            .literal
+            .as_ref()
+            .map_or(String::new(), |literal| literal.print())
+    }
+
+    #[test]
+    fn interpolation_splits_the_string() {
+        let (tokens, errors) = scan("\"a${x}b\"");
+        assert!(errors.is_empty());
+        assert_eq!(
+            types(&tokens),
+            [
+                TokenType::INTERPOLATION,
+                TokenType::IDENTIFIER,
+                TokenType::STRING,
+                TokenType::EOF
+            ]
+        );
+        assert_eq!(text(&tokens[0]), "a");
+        assert_eq!(text(&tokens[2]), "b");
+    }
+
+    #[test]
+    fn braces_inside_an_interpolation_do_not_close_it() {
+        let (tokens, errors) = scan("\"${ {1: 2}[1] }!\"");
+        assert!(errors.is_empty());
+        let braces = types(&tokens)
+            .into_iter()
+            .filter(|token_type| *token_type == TokenType::RightBrace)
+            .count();
+        assert_eq!(braces, 1);
+        assert_eq!(tokens[tokens.len() - 2].token_type, TokenType::STRING);
+        assert_eq!(text(&tokens[tokens.len() - 2]), "!");
+    }
+
+    #[test]
+    fn nested_interpolations() {
+        let (tokens, errors) = scan("\"a${\"b${x}c\"}d\"");
+        assert!(errors.is_empty());
+        assert_eq!(
+            types(&tokens),
+            [
+                TokenType::INTERPOLATION,
+                TokenType::INTERPOLATION,
+                TokenType::IDENTIFIER,
+                TokenType::STRING,
+                TokenType::STRING,
+                TokenType::EOF
+            ]
+        );
+        let parts: Vec<_> = tokens.iter().map(text).collect();
+        assert_eq!(parts, ["a", "b", "", "c", "d", ""]);
+    }
+
+    #[test]
+    fn bad_escape_before_an_interpolation_is_reported_once() {
+        let (tokens, errors) = scan("\"\\q${x}\"; print 1;");
+        assert_eq!(errors.len(), 1);
+        assert!(matches!(
+            errors.iter().next(),
+            Some(Errors::InvalidEscape('q', _))
+        ));
+        assert!(types(&tokens).contains(&TokenType::PRINT));
+    }
+
+    #[test]
+    fn escaped_dollar_is_not_an_interpolation() {
+        let (tokens, errors) = scan("\"\\${x}\"");
+        assert!(errors.is_empty());
+        assert_eq!(types(&tokens), [TokenType::STRING, TokenType::EOF]);
+        assert_eq!(text(&tokens[0]), "${x}");
+    }
+
+    #[test]
+    fn raw_string_keeps_interpolation_text() {
+        let (tokens, errors) = scan("r\"${x}\"");
+        assert!(errors.is_empty());
+        assert_eq!(types(&tokens), [TokenType::STRING, TokenType::EOF]);
+        assert_eq!(text(&tokens[0]), "${x}");
+    }
+}
@@ -1,21 +1,35 @@
+pub mod bigint;
+pub mod callable;
+pub mod class;
+pub mod environment;
 pub mod error_handler;
 pub mod lexer;
+pub mod map;
+pub mod numeric;
 pub mod parser;
+pub mod rational;
 
 pub mod tokens {
     use std::fmt::{self, Display};
 
+    use crate::bigint::BigInt;
+
     #[derive(Debug, Clone)]
     pub enum LiteralType {
         String(String),
-        Number(f32),
+        Integer(i64),
+        // an integer literal too large for an i64
+        BigInteger(BigInt),
+        Float(f64),
     }
 
     impl LiteralType {
         pub fn print(&self) -> String {
             match self {
                 LiteralType::String(x) => x.to_string(),
-                LiteralType::Number(x) => x.to_string(),
+                LiteralType::Integer(x) => x.to_string(),
+                LiteralType::BigInteger(x) => x.to_string(),
+                LiteralType::Float(x) => x.to_string(),
             }
         }
     }
@@ -27,6 +41,8 @@ pub mod tokens {
         RightPara,
         LeftBrace,
         RightBrace,
+        LeftBracket,
+        RightBracket,
         COMMA,
         DOT,
         MINUS,
@@ -34,6 +50,13 @@ pub mod tokens {
         SEMICOLON,
         SLASH,
         STAR,
+        PERCENT,
+        AMPERSAND,
+        PIPE,
+        CARET,
+        TILDE,
+        QUESTION,
+        COLON,
 
         BANG,
         BangEqual,
@@ -43,13 +66,29 @@ pub mod tokens {
         GreaterEqual,
         LESS,
         LessEqual,
+        StarStar,
+        SlashSlash,
+        LessLess,
+        GreaterGreater,
+        PlusEqual,
+        MinusEqual,
+        StarEqual,
+        SlashEqual,
+        QuestionQuestion,
+        QuestionDot,
 
         IDENTIFIER,
         STRING,
+        // the text of a String up to an embedded `${`, the rest comes as later tokens
+        INTERPOLATION,
         NUMBER,
+        // `'name`, marks a loop for break and continue
+        LABEL,
 
         AND,
+        BREAK,
         CLASS,
+        CONTINUE,
         ELSE,
         FALSE,
         FUN,
@@ -59,11 +98,14 @@ pub mod tokens {
         OR,
         PRINT,
         RETURN,
+        SUPER,
         THIS,
         TRUE,
         LET,
         WHILE,
 
+        // text the lexer could not make sense of, already reported
+        ERROR,
         EOF,
     }
     // impl Display for TokenType {
@@ -71,12 +113,46 @@ pub mod tokens {
     //         write!(f, "{}", self)
     //     }
     // }
+    /// A range of source text: `start..end` in bytes, plus the line and column
+    /// (both counted from 1, columns in characters) of its first character and
+    /// of the position just past its last one.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+    pub struct Span {
+        pub start: usize,
+        pub end: usize,
+        pub start_line: usize,
+        pub start_column: usize,
+        pub end_line: usize,
+        pub end_column: usize,
+    }
+
+    impl Span {
+        /// The smallest span covering both `self` and `other`.
+        pub fn merge(self, other: Span) -> Span {
+            let first = if self.start <= other.start {
+                self
+            } else {
+                other
+            };
+            let last = if self.end >= other.end { self } else { other };
+            Span {
+                start: first.start,
+                end: last.end,
+                start_line: first.start_line,
+                start_column: first.start_column,
+                end_line: last.end_line,
+                end_column: last.end_column,
+            }
+        }
+    }
+
     #[derive(Debug, Clone)]
     pub struct Token {
         pub token_type: TokenType,
         pub text: String,
         pub literal: Option<LiteralType>,
         pub line: usize,
+        pub span: Span,
     }
 
     impl Token {
@@ -84,13 +160,14 @@ pub mod tokens {
             token_type: TokenType,
             text: String,
             literal: Option<LiteralType>,
-            line: usize,
+            span: Span,
         ) -> Self {
             Token {
                 token_type,
                 text,
                 literal,
-                line,
+                line: span.start_line,
+                span,
             }
         }
     }
@@ -3,18 +3,29 @@ use std::{
     fs::{self},
     io,
     path::PathBuf,
+    process, thread,
 };
 
 use psudo::{
+    error_handler::ErrorSet,
     lexer::Lexer,
-    parser::{Expression, ExpressionVistor, Interpret, Parser, PrettyPrint},
-    tokens::{LiteralType, Token, TokenType},
+    parser::{Interpret, Parser},
 };
 
+// exit codes follow sysexits.h so callers can tell bad programs from failing ones
+const EXIT_DATA_ERROR: i32 = 65;
+const EXIT_SOFTWARE: i32 = 70;
+
+// every call in a script nests several rust calls, so the interpreter runs on
+// a thread with room for the deepest recursion it allows
+const INTERPRETER_STACK_SIZE: usize = 256 * 1024 * 1024;
+
 #[derive(Default)]
 struct Config {
     is_interactive: bool,
     file_path: PathBuf,
+    lenient: bool,
+    exact: bool,
 }
 
 // impl Default for Config {
@@ -30,7 +41,7 @@ fn main() {
     let mut program_conf: Config = Config::default();
 
     //TODO: refactor CLI
-    let mut flags: Args = env::args().into_iter();
+    let mut flags: Args = env::args();
 
     //skip the default exe path arg
     flags.next();
@@ -39,6 +50,8 @@ fn main() {
         while let Some(flag) = flags.next() {
             match flag.as_str() {
                 "-i" | "--interactive" => program_conf.is_interactive = true,
+                "-l" | "--lenient" => program_conf.lenient = true,
+                "-e" | "--exact" => program_conf.exact = true,
                 "-f" | "--file" => {
                     if let Some(path) = flags.next() {
                         program_conf.file_path = PathBuf::from(path);
@@ -56,16 +69,29 @@ fn main() {
         program_conf.is_interactive = true;
     }
 
-    match program_conf {
-        Config {
-            is_interactive: true,
-            ..
-        } => run_prompt(),
-        Config {
-            is_interactive: false,
-            file_path: path,
-        } => run_file(path),
-    };
+    let interpreter = thread::Builder::new()
+        .stack_size(INTERPRETER_STACK_SIZE)
+        .spawn(move || {
+            let mut interpreter = Interpret::new()
+                .lenient(program_conf.lenient)
+                .exact(program_conf.exact);
+
+            match program_conf {
+                Config {
+                    is_interactive: true,
+                    ..
+                } => run_prompt(&mut interpreter),
+                Config {
+                    is_interactive: false,
+                    file_path: path,
+                    ..
+                } => run_file(path, &mut interpreter),
+            };
+        })
+        .expect("Could not start the interpreter");
+    if interpreter.join().is_err() {
+        process::exit(EXIT_SOFTWARE);
+    }
 }
 fn print_help() {
     println!(
@@ -74,19 +100,23 @@ fn print_help() {
               \n\t-h,--help\t\tDisplay this help\
               \n\t-i,--interactive\tStart interactive Prompt (default if no file is passed)\
               \n\t-f,--file\t\tInput file to interpret\
+              \n\t-l,--lenient\t\tCoerce operands of mismatched types instead of failing\
+              \n\t-e,--exact\t\tDivide integers into exact fractions instead of truncating\
 "
     )
 }
 
-fn run_file(path: PathBuf) {
+fn run_file(path: PathBuf, interpreter: &mut Interpret) {
     println!("\nfrom run file {}\n", path.display());
 
     let source = fs::read_to_string(path).expect("Could not open file");
 
-    run(source);
+    if let Err(code) = run(source, interpreter) {
+        process::exit(code);
+    }
 }
 
-fn run_prompt() {
+fn run_prompt(interpreter: &mut Interpret) {
     loop {
         let mut line = String::new();
         println!(">>");
@@ -96,30 +126,26 @@ fn run_prompt() {
             break;
         };
 
-        run(line)
+        let _ = run(line, interpreter);
         // debug_exp_print()
     }
 }
 
-fn run(source: String) {
-    let mut lexer = Lexer::new(source.trim().to_owned());
-    let token_list_result = lexer.scan_tokens();
-    match token_list_result {
-        Ok(token_list) => {
-            // println!("\nTokens :\n***");
-            // for token in token_list {
-            //     println!("{}", token);
-            // }
-            let _ = Parser::new(token_list)
-                .expression()
-                .map(|exp| {
-                    // println!("Expr: {}", PrettyPrint.vist_expr(exp));
-                    println!("eval: {}", Interpret.vist_expr(exp))
-                })
-                .map_err(|err| println!("{}", err));
-        }
-        Err(_) => todo!(),
+fn run(source: String, interpreter: &mut Interpret) -> Result<(), i32> {
+    let source = source.trim_end();
+    let mut lexer = Lexer::new(source.to_owned());
+    let mut errors = ErrorSet::default();
+    // tokens stream straight from the lexer into the parser
+    let (program, parse_errors) = Parser::new(lexer.tokens(&mut errors)).parse();
+    errors.append(parse_errors);
+    if errors.had_error() {
+        eprint!("{}", errors.render(source));
+        return Err(EXIT_DATA_ERROR);
     }
+    interpreter.interpret(&program).map_err(|err| {
+        eprint!("{}", err.diagnostic().render(source));
+        EXIT_SOFTWARE
+    })
 }
 
 // fn debug_exp_print() {
@@ -0,0 +1,97 @@
+use std::{
+    collections::HashMap,
+    fmt::{self, Display},
+};
+
+use crate::{bigint::BigInt, parser::DataTypes, rational::Rational};
+
+// the values that can be Map keys, the ones with a stable hash and equality.
+// Floats are left out as 1.0 == 1 would need them to hash like Ints
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Key {
+    Int(i64),
+    BigInt(BigInt),
+    Rational(Rational),
+    Bool(bool),
+    String(String),
+    Nil,
+}
+
+impl Key {
+    /// `None` when the value can not be hashed.
+    pub fn new(value: &DataTypes) -> Option<Self> {
+        match value {
+            DataTypes::Int(num) => Some(Key::Int(*num)),
+            DataTypes::BigInt(num) => Some(Key::BigInt(num.clone())),
+            DataTypes::Rational(num) => Some(Key::Rational(num.clone())),
+            DataTypes::Bool(bool) => Some(Key::Bool(*bool)),
+            DataTypes::String(string) => Some(Key::String(string.clone())),
+            DataTypes::Nil => Some(Key::Nil),
+            _ => None,
+        }
+    }
+}
+
+impl From<Key> for DataTypes {
+    fn from(value: Key) -> Self {
+        match value {
+            Key::Int(num) => DataTypes::Int(num),
+            Key::BigInt(num) => DataTypes::BigInt(num),
+            Key::Rational(num) => DataTypes::Rational(num),
+            Key::Bool(bool) => DataTypes::Bool(bool),
+            Key::String(string) => DataTypes::String(string),
+            Key::Nil => DataTypes::Nil,
+        }
+    }
+}
+
+impl Display for Key {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", DataTypes::from(self.clone()))
+    }
+}
+
+// entries stay in the order their keys were first inserted, the index maps
+// each key to its entry
+#[derive(Debug, Clone, Default)]
+pub struct Map {
+    entries: Vec<(Key, DataTypes)>,
+    index: HashMap<Key, usize>,
+}
+
+impl Map {
+    pub fn new() -> Self {
+        Map::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn get(&self, key: &Key) -> Option<&DataTypes> {
+        self.index.get(key).map(|&i| &self.entries[i].1)
+    }
+
+    pub fn contains_key(&self, key: &Key) -> bool {
+        self.index.contains_key(key)
+    }
+
+    // a key already in the Map keeps its place
+    pub fn insert(&mut self, key: Key, value: DataTypes) {
+        match self.index.get(&key) {
+            Some(&i) => self.entries[i].1 = value,
+            None => {
+                self.index.insert(key.clone(), self.entries.len());
+                self.entries.push((key, value));
+            }
+        }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &(Key, DataTypes)> {
+        self.entries.iter()
+    }
+}
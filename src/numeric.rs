@@ -0,0 +1,411 @@
+use crate::{
+    bigint::BigInt,
+    error_handler::{RuntimeError, RuntimeErrorKind},
+    parser::DataTypes,
+    rational::Rational,
+    tokens::{Token, TokenType},
+};
+
+// the most bits a shift may move by and an exact power may grow to, past that
+// the BigInt arithmetic would run for too long
+pub const MAX_BITS: u32 = 1 << 18;
+
+// arithmetic and comparison between two numbers. the operands meet at the
+// wider of their two types, Int < BigInt < Rational < Float
+pub fn number_expr(
+    a: &DataTypes,
+    operator: &Token,
+    b: &DataTypes,
+    exact: bool,
+) -> Result<DataTypes, RuntimeError> {
+    if !is_number(a) || !is_number(b) {
+        return Err(RuntimeError::new(
+            operator,
+            RuntimeErrorKind::InvalidOperands("Numbers"),
+        ));
+    }
+    match operator.token_type {
+        TokenType::AMPERSAND
+        | TokenType::PIPE
+        | TokenType::CARET
+        | TokenType::LessLess
+        | TokenType::GreaterGreater => return bitwise_expr(a, operator, b),
+        TokenType::StarStar => return power(a, operator, b, exact),
+        _ => {}
+    }
+    match (a, b) {
+        (DataTypes::Float(_), _) | (_, DataTypes::Float(_)) => {
+            float_expr(to_f64(a), operator, to_f64(b))
+        }
+        (DataTypes::Rational(_), _) | (_, DataTypes::Rational(_)) => {
+            rational_expr(&to_rational(a), operator, &to_rational(b))
+        }
+        (DataTypes::Int(a), DataTypes::Int(b)) => int_expr(*a, operator, *b, exact),
+        _ => big_expr(&to_big(a), operator, &to_big(b), exact),
+    }
+}
+
+pub fn is_number(value: &DataTypes) -> bool {
+    matches!(
+        value,
+        DataTypes::Int(_) | DataTypes::BigInt(_) | DataTypes::Rational(_) | DataTypes::Float(_)
+    )
+}
+
+pub fn to_f64(value: &DataTypes) -> f64 {
+    match value {
+        DataTypes::Int(num) => *num as f64,
+        DataTypes::BigInt(num) => num.to_f64(),
+        DataTypes::Rational(num) => num.to_f64(),
+        DataTypes::Float(num) => *num,
+        _ => f64::NAN,
+    }
+}
+
+fn to_big(value: &DataTypes) -> BigInt {
+    match value {
+        DataTypes::Int(num) => BigInt::from(*num),
+        DataTypes::BigInt(num) => num.clone(),
+        _ => unreachable!("only Ints and BigInts are widened to a BigInt"),
+    }
+}
+
+fn to_rational(value: &DataTypes) -> Rational {
+    match value {
+        DataTypes::Rational(num) => num.clone(),
+        _ => Rational::from(to_big(value)),
+    }
+}
+
+// a BigInt that fits goes back to being an Int, so each value has one form
+fn from_big(value: BigInt) -> DataTypes {
+    match value.to_i64() {
+        Some(num) => DataTypes::Int(num),
+        None => DataTypes::BigInt(value),
+    }
+}
+
+fn bits(value: &DataTypes) -> u64 {
+    match value {
+        DataTypes::Rational(num) => num.numerator().bits().max(num.denominator().bits()),
+        _ => to_big(value).bits(),
+    }
+}
+
+fn from_rational(value: Rational) -> DataTypes {
+    match value.is_integer() {
+        true => from_big(value.numerator().clone()),
+        false => DataTypes::Rational(value),
+    }
+}
+
+// `/` on two Ints truncates towards zero unless exact mode keeps the fraction,
+// anything that overflows an i64 is done again as a BigInt
+fn int_expr(a: i64, operator: &Token, b: i64, exact: bool) -> Result<DataTypes, RuntimeError> {
+    let result =
+        match operator.token_type {
+            TokenType::MINUS => a.checked_sub(b),
+            TokenType::PLUS => a.checked_add(b),
+            TokenType::STAR => a.checked_mul(b),
+            TokenType::SLASH if b == 0 => {
+                return Err(RuntimeError::new(
+                    operator,
+                    RuntimeErrorKind::DivisionByZero,
+                ));
+            }
+            TokenType::SLASH if exact && a.checked_rem(b) != Some(0) => None,
+            TokenType::SLASH => a.checked_div(b),
+            TokenType::SlashSlash | TokenType::PERCENT if b == 0 => {
+                return Err(RuntimeError::new(
+                    operator,
+                    RuntimeErrorKind::DivisionByZero,
+                ));
+            }
+            // `//` rounds down and `%` takes the sign of the divisor, so that
+            // a == (a // b) * b + a % b
+            TokenType::SlashSlash => {
+                a.checked_div(b)
+                    .map(|quotient| match a % b != 0 && (a < 0) != (b < 0) {
+                        true => quotient - 1,
+                        false => quotient,
+                    })
+            }
+            TokenType::PERCENT => a.checked_rem(b).map(|remainder| {
+                match remainder != 0 && (remainder < 0) != (b < 0) {
+                    true => remainder + b,
+                    false => remainder,
+                }
+            }),
+            TokenType::LessEqual => return Ok(DataTypes::Bool(a <= b)),
+            TokenType::LESS => return Ok(DataTypes::Bool(a < b)),
+            TokenType::GreaterEqual => return Ok(DataTypes::Bool(a >= b)),
+            TokenType::GREATER => return Ok(DataTypes::Bool(a > b)),
+            _ => {
+                return Err(RuntimeError::new(
+                    operator,
+                    RuntimeErrorKind::UnsupportedOperator,
+                ));
+            }
+        };
+    match result {
+        Some(num) => Ok(DataTypes::Int(num)),
+        None => big_expr(&BigInt::from(a), operator, &BigInt::from(b), exact),
+    }
+}
+
+fn big_expr(
+    a: &BigInt,
+    operator: &Token,
+    b: &BigInt,
+    exact: bool,
+) -> Result<DataTypes, RuntimeError> {
+    Ok(match operator.token_type {
+        TokenType::MINUS => from_big(a - b),
+        TokenType::PLUS => from_big(a + b),
+        TokenType::STAR => from_big(a * b),
+        TokenType::SLASH => {
+            let Some((quotient, remainder)) = a.div_rem(b) else {
+                return Err(RuntimeError::new(
+                    operator,
+                    RuntimeErrorKind::DivisionByZero,
+                ));
+            };
+            if exact && !remainder.is_zero() {
+                return rational_expr(
+                    &Rational::from(a.clone()),
+                    operator,
+                    &Rational::from(b.clone()),
+                );
+            }
+            from_big(quotient)
+        }
+        TokenType::SlashSlash | TokenType::PERCENT => {
+            let Some((quotient, remainder)) = a.div_rem_floor(b) else {
+                return Err(RuntimeError::new(
+                    operator,
+                    RuntimeErrorKind::DivisionByZero,
+                ));
+            };
+            match operator.token_type {
+                TokenType::SlashSlash => from_big(quotient),
+                _ => from_big(remainder),
+            }
+        }
+        TokenType::LessEqual => DataTypes::Bool(a <= b),
+        TokenType::LESS => DataTypes::Bool(a < b),
+        TokenType::GreaterEqual => DataTypes::Bool(a >= b),
+        TokenType::GREATER => DataTypes::Bool(a > b),
+        _ => {
+            return Err(RuntimeError::new(
+                operator,
+                RuntimeErrorKind::UnsupportedOperator,
+            ));
+        }
+    })
+}
+
+fn rational_expr(a: &Rational, operator: &Token, b: &Rational) -> Result<DataTypes, RuntimeError> {
+    Ok(match operator.token_type {
+        TokenType::MINUS => from_rational(a - b),
+        TokenType::PLUS => from_rational(a + b),
+        TokenType::STAR => from_rational(a * b),
+        TokenType::SLASH | TokenType::SlashSlash | TokenType::PERCENT => {
+            let Some(quotient) = a.checked_div(b) else {
+                return Err(RuntimeError::new(
+                    operator,
+                    RuntimeErrorKind::DivisionByZero,
+                ));
+            };
+            match operator.token_type {
+                TokenType::SLASH => from_rational(quotient),
+                TokenType::SlashSlash => from_big(quotient.floor()),
+                _ => from_rational(a - &(b * &Rational::from(quotient.floor()))),
+            }
+        }
+        TokenType::LessEqual => DataTypes::Bool(a <= b),
+        TokenType::LESS => DataTypes::Bool(a < b),
+        TokenType::GreaterEqual => DataTypes::Bool(a >= b),
+        TokenType::GREATER => DataTypes::Bool(a > b),
+        _ => {
+            return Err(RuntimeError::new(
+                operator,
+                RuntimeErrorKind::UnsupportedOperator,
+            ));
+        }
+    })
+}
+
+fn float_expr(a: f64, operator: &Token, b: f64) -> Result<DataTypes, RuntimeError> {
+    Ok(match operator.token_type {
+        TokenType::MINUS => DataTypes::Float(a - b),
+        TokenType::PLUS => DataTypes::Float(a + b),
+        TokenType::SLASH | TokenType::SlashSlash | TokenType::PERCENT if b == 0.0 => {
+            return Err(RuntimeError::new(
+                operator,
+                RuntimeErrorKind::DivisionByZero,
+            ));
+        }
+        TokenType::SLASH => DataTypes::Float(a / b),
+        TokenType::SlashSlash => DataTypes::Float((a / b).floor()),
+        TokenType::PERCENT => {
+            let remainder = a % b;
+            match remainder != 0.0 && (remainder < 0.0) != (b < 0.0) {
+                true => DataTypes::Float(remainder + b),
+                false => DataTypes::Float(remainder),
+            }
+        }
+        TokenType::STAR => DataTypes::Float(a * b),
+        TokenType::LessEqual => DataTypes::Bool(a <= b),
+        TokenType::LESS => DataTypes::Bool(a < b),
+        TokenType::GreaterEqual => DataTypes::Bool(a >= b),
+        TokenType::GREATER => DataTypes::Bool(a > b),
+        _ => {
+            return Err(RuntimeError::new(
+                operator,
+                RuntimeErrorKind::UnsupportedOperator,
+            ));
+        }
+    })
+}
+
+// an integer to an integer power stays exact, a negative power is a Float
+// unless exact mode makes it a Rational
+fn power(
+    a: &DataTypes,
+    operator: &Token,
+    b: &DataTypes,
+    exact: bool,
+) -> Result<DataTypes, RuntimeError> {
+    let exponent = match b {
+        DataTypes::Int(exponent) => u32::try_from(exponent.unsigned_abs()).ok(),
+        _ => None,
+    };
+    let negative = matches!(b, DataTypes::Int(exponent) if *exponent < 0);
+    // a negative power divides by the base, like `/` it can not divide by zero
+    if to_f64(a) == 0.0 && to_f64(b) < 0.0 {
+        return Err(RuntimeError::new(
+            operator,
+            RuntimeErrorKind::DivisionByZero,
+        ));
+    }
+    // an exact result has at least (bits - 1) * exponent bits
+    let exact_result = !matches!(a, DataTypes::Float(_)) && (!negative || exact);
+    if let Some(exponent) = exponent.filter(|_| exact_result) {
+        if bits(a).saturating_sub(1) * exponent as u64 > MAX_BITS as u64 {
+            return Err(RuntimeError::new(
+                operator,
+                RuntimeErrorKind::NumberTooLarge,
+            ));
+        }
+    }
+    Ok(match (a, exponent) {
+        (DataTypes::Float(_), _) | (_, None) => DataTypes::Float(to_f64(a).powf(to_f64(b))),
+        (_, Some(exponent)) if negative && exact => {
+            let base = to_rational(a).pow(exponent);
+            from_rational(
+                Rational::from(BigInt::from(1))
+                    .checked_div(&base)
+                    .expect("a zero base is rejected above"),
+            )
+        }
+        (_, Some(_)) if negative => DataTypes::Float(to_f64(a).powf(to_f64(b))),
+        (DataTypes::Int(num), Some(exponent)) => match num.checked_pow(exponent) {
+            Some(num) => DataTypes::Int(num),
+            None => from_big(BigInt::from(*num).pow(exponent)),
+        },
+        (DataTypes::Rational(num), Some(exponent)) => from_rational(num.pow(exponent)),
+        (_, Some(exponent)) => from_big(to_big(a).pow(exponent)),
+    })
+}
+
+// `& | ^` work on Ints, shifts also take BigInts and grow into them
+fn bitwise_expr(a: &DataTypes, operator: &Token, b: &DataTypes) -> Result<DataTypes, RuntimeError> {
+    let is_integer = |value: &DataTypes| matches!(value, DataTypes::Int(_) | DataTypes::BigInt(_));
+    if !is_integer(a) || !is_integer(b) {
+        return Err(RuntimeError::new(
+            operator,
+            RuntimeErrorKind::InvalidOperands("Ints"),
+        ));
+    }
+    let shift = match b {
+        DataTypes::Int(amount) => u32::try_from(*amount)
+            .ok()
+            .filter(|amount| *amount <= MAX_BITS),
+        _ => None,
+    };
+    Ok(match (operator.token_type.clone(), a, b) {
+        (TokenType::AMPERSAND, DataTypes::Int(a), DataTypes::Int(b)) => DataTypes::Int(a & b),
+        (TokenType::PIPE, DataTypes::Int(a), DataTypes::Int(b)) => DataTypes::Int(a | b),
+        (TokenType::CARET, DataTypes::Int(a), DataTypes::Int(b)) => DataTypes::Int(a ^ b),
+        (TokenType::LessLess | TokenType::GreaterGreater, ..) if shift.is_none() => {
+            return Err(RuntimeError::new(operator, RuntimeErrorKind::InvalidShift));
+        }
+        (TokenType::LessLess, DataTypes::Int(num), _) => {
+            match shift
+                .filter(|amount| *amount < 63)
+                .and_then(|amount| num.checked_mul(1 << amount))
+            {
+                Some(num) => DataTypes::Int(num),
+                None => from_big(&to_big(a) << shift.unwrap()),
+            }
+        }
+        (TokenType::LessLess, ..) => from_big(&to_big(a) << shift.unwrap()),
+        // shifting right rounds down, like dividing by a power of two
+        (TokenType::GreaterGreater, DataTypes::Int(num), _) => {
+            DataTypes::Int(num >> shift.unwrap().min(63))
+        }
+        (TokenType::GreaterGreater, ..) => from_big(&to_big(a) >> shift.unwrap()),
+        _ => {
+            return Err(RuntimeError::new(
+                operator,
+                RuntimeErrorKind::InvalidOperands("Ints that fit in 64 bits"),
+            ));
+        }
+    })
+}
+
+// `~x` is `-x - 1`, which also holds for a BigInt
+pub fn bit_not(value: &DataTypes, operator: &Token) -> Result<DataTypes, RuntimeError> {
+    match value {
+        DataTypes::Int(num) => Ok(DataTypes::Int(!num)),
+        DataTypes::BigInt(num) => Ok(from_big(&-num - &BigInt::from(1))),
+        _ => Err(RuntimeError::new(
+            operator,
+            RuntimeErrorKind::InvalidOperand("an Int"),
+        )),
+    }
+}
+
+pub fn negate(value: &DataTypes, operator: &Token) -> Result<DataTypes, RuntimeError> {
+    match value {
+        DataTypes::Int(num) => Ok(match num.checked_neg() {
+            Some(num) => DataTypes::Int(num),
+            None => from_big(-&BigInt::from(*num)),
+        }),
+        DataTypes::BigInt(num) => Ok(from_big(-num)),
+        DataTypes::Rational(num) => Ok(DataTypes::Rational(-num)),
+        DataTypes::Float(num) => Ok(DataTypes::Float(-num)),
+        _ => Err(RuntimeError::new(
+            operator,
+            RuntimeErrorKind::InvalidOperand("a Number"),
+        )),
+    }
+}
+
+// lenient mode reads bools and nil as the Ints 1 and 0, anything else is NaN
+pub fn coerce(value: &DataTypes) -> DataTypes {
+    match value {
+        DataTypes::Int(_) | DataTypes::BigInt(_) | DataTypes::Rational(_) | DataTypes::Float(_) => {
+            value.clone()
+        }
+        DataTypes::Bool(bool) => DataTypes::Int(*bool as i64),
+        DataTypes::Nil => DataTypes::Int(0),
+        DataTypes::String(_)
+        | DataTypes::Function(_)
+        | DataTypes::Native(_)
+        | DataTypes::Class(_)
+        | DataTypes::Instance(_)
+        | DataTypes::List(_)
+        | DataTypes::Map(_) => DataTypes::Float(f64::NAN),
+    }
+}
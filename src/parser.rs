@@ -1,45 +1,190 @@
-use std::{fmt::Display, iter::Peekable, vec::IntoIter};
+use std::{cell::RefCell, collections::HashMap, fmt::Display, iter::Peekable, rc::Rc};
 
 use crate::{
-    error_handler::Errors,
-    lexer::Lexer,
-    tokens::{self, LiteralType, Token, TokenType},
+    bigint::BigInt,
+    callable::{self, Callable, Function, NativeFunction},
+    class::{Class, Instance},
+    environment::Environment,
+    error_handler::{ErrorSet, Errors, RuntimeError, RuntimeErrorKind},
+    map::{Key, Map},
+    numeric,
+    rational::Rational,
+    tokens::{LiteralType, Span, Token, TokenType},
 };
 
+#[derive(Debug, Clone)]
 pub enum DataTypes {
-    Number(f32),
+    Int(i64),
+    BigInt(BigInt),
+    Rational(Rational),
+    Float(f64),
     Bool(bool),
+    String(String),
+    Nil,
+    Function(Rc<Function>),
+    Native(Rc<NativeFunction>),
+    Class(Rc<Class>),
+    Instance(Rc<RefCell<Instance>>),
+    // shared, so every variable holding the List sees changes made through another
+    List(Rc<RefCell<Vec<DataTypes>>>),
+    Map(Rc<RefCell<Map>>),
 }
 
 impl Display for DataTypes {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            DataTypes::Number(num) => write!(f, "float_datatype: {num}"),
-            DataTypes::Bool(bool) => write!(f, "bool_datatype: {bool}"),
+            DataTypes::Int(num) => write!(f, "{num}"),
+            DataTypes::BigInt(num) => write!(f, "{num}"),
+            DataTypes::Rational(num) => write!(f, "{num}"),
+            // a whole Float keeps its `.0` so it is not mistaken for an Int
+            DataTypes::Float(num) if num.fract() == 0.0 && num.is_finite() => {
+                write!(f, "{num:.1}")
+            }
+            DataTypes::Float(num) => write!(f, "{num}"),
+            DataTypes::Bool(bool) => write!(f, "{bool}"),
+            DataTypes::String(string) => write!(f, "{string}"),
+            DataTypes::Nil => write!(f, "nil"),
+            DataTypes::Function(function) => write!(f, "<fn {}>", function.name()),
+            DataTypes::Native(native) => write!(f, "<native fn {}>", native.name),
+            DataTypes::Class(class) => write!(f, "{}", class.name),
+            DataTypes::Instance(instance) => {
+                write!(f, "<{} instance>", instance.borrow().class.name)
+            }
+            DataTypes::List(_) | DataTypes::Map(_) => fmt_nested(self, f, &mut Vec::new()),
         }
     }
 }
 
-impl From<DataTypes> for f32 {
-    fn from(value: DataTypes) -> Self {
-        match value {
-            DataTypes::Number(num) => num,
-            DataTypes::Bool(true) => 1.0,
-            DataTypes::Bool(false) => 0.0,
+// Lists and Maps can hold themselves, the ones being printed are kept so a
+// cycle shows as `[...]` or `{...}` instead of recursing forever
+fn fmt_nested(
+    value: &DataTypes,
+    f: &mut std::fmt::Formatter<'_>,
+    seen: &mut Vec<*const ()>,
+) -> std::fmt::Result {
+    match value {
+        DataTypes::List(list) => {
+            let pointer = Rc::as_ptr(list) as *const ();
+            if seen.contains(&pointer) {
+                return write!(f, "[...]");
+            }
+            seen.push(pointer);
+            write!(f, "[")?;
+            for (i, element) in list.borrow().iter().enumerate() {
+                if i > 0 {
+                    write!(f, ", ")?;
+                }
+                fmt_nested(element, f, seen)?;
+            }
+            seen.pop();
+            write!(f, "]")
+        }
+        DataTypes::Map(map) => {
+            let pointer = Rc::as_ptr(map) as *const ();
+            if seen.contains(&pointer) {
+                return write!(f, "{{...}}");
+            }
+            seen.push(pointer);
+            write!(f, "{{")?;
+            for (i, (key, value)) in map.borrow().iter().enumerate() {
+                if i > 0 {
+                    write!(f, ", ")?;
+                }
+                write!(f, "{key}: ")?;
+                fmt_nested(value, f, seen)?;
+            }
+            seen.pop();
+            write!(f, "}}")
+        }
+        _ => write!(f, "{value}"),
+    }
+}
+
+// values are equal only when they have the same type, functions, classes and
+// instances are compared by identity, Lists and Maps by their elements
+impl PartialEq for DataTypes {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (DataTypes::Int(a), DataTypes::Int(b)) => a == b,
+            (DataTypes::Float(a), DataTypes::Float(b)) => a == b,
+            (DataTypes::BigInt(a), DataTypes::BigInt(b)) => a == b,
+            (DataTypes::Rational(a), DataTypes::Rational(b)) => a == b,
+            // exact numbers are kept in their narrowest type, so only a Float
+            // can equal a number of another type
+            (DataTypes::Float(a), other) | (other, DataTypes::Float(a))
+                if numeric::is_number(other) =>
+            {
+                *a == numeric::to_f64(other)
+            }
+            (DataTypes::Bool(a), DataTypes::Bool(b)) => a == b,
+            (DataTypes::String(a), DataTypes::String(b)) => a == b,
+            (DataTypes::Nil, DataTypes::Nil) => true,
+            (DataTypes::Function(a), DataTypes::Function(b)) => Rc::ptr_eq(a, b),
+            (DataTypes::Native(a), DataTypes::Native(b)) => Rc::ptr_eq(a, b),
+            (DataTypes::Class(a), DataTypes::Class(b)) => Rc::ptr_eq(a, b),
+            (DataTypes::Instance(a), DataTypes::Instance(b)) => Rc::ptr_eq(a, b),
+            (DataTypes::List(_), DataTypes::List(_)) | (DataTypes::Map(_), DataTypes::Map(_)) => {
+                eq_nested(self, other, &mut Vec::new())
+            }
+            _ => false,
         }
     }
 }
-impl From<f32> for DataTypes {
-    fn from(value: f32) -> Self {
-        DataTypes::Number(value)
+
+// the pairs of Lists or Maps being compared are kept, meeting a pair again
+// means a cycle that has matched so far, so it counts as equal
+fn eq_nested(a: &DataTypes, b: &DataTypes, seen: &mut Vec<(*const (), *const ())>) -> bool {
+    let pair = match (a, b) {
+        (DataTypes::List(a), DataTypes::List(b)) if Rc::ptr_eq(a, b) => return true,
+        (DataTypes::Map(a), DataTypes::Map(b)) if Rc::ptr_eq(a, b) => return true,
+        (DataTypes::List(a), DataTypes::List(b)) => {
+            (Rc::as_ptr(a) as *const (), Rc::as_ptr(b) as *const ())
+        }
+        (DataTypes::Map(a), DataTypes::Map(b)) => {
+            (Rc::as_ptr(a) as *const (), Rc::as_ptr(b) as *const ())
+        }
+        _ => return a == b,
+    };
+    if seen.contains(&pair) {
+        return true;
     }
+    seen.push(pair);
+    let equal = match (a, b) {
+        (DataTypes::List(a), DataTypes::List(b)) => {
+            let (a, b) = (a.borrow(), b.borrow());
+            a.len() == b.len() && a.iter().zip(b.iter()).all(|(a, b)| eq_nested(a, b, seen))
+        }
+        (DataTypes::Map(a), DataTypes::Map(b)) => {
+            // entries match whatever order they were added in
+            let (a, b) = (a.borrow(), b.borrow());
+            a.len() == b.len()
+                && a.iter().all(|(key, value)| {
+                    b.get(key)
+                        .is_some_and(|other| eq_nested(value, other, seen))
+                })
+        }
+        _ => unreachable!("only Lists and Maps get this far"),
+    };
+    seen.pop();
+    equal
 }
+
 impl From<DataTypes> for bool {
     fn from(value: DataTypes) -> Self {
         match value {
             DataTypes::Bool(bool) => bool,
-            DataTypes::Number(0.0) => false,
-            DataTypes::Number(_) => true,
+            DataTypes::Int(num) => num != 0,
+            DataTypes::Float(num) => num != 0.0,
+            DataTypes::BigInt(num) => !num.is_zero(),
+            DataTypes::Rational(num) => !num.is_zero(),
+            DataTypes::String(string) => !string.is_empty(),
+            DataTypes::List(list) => !list.borrow().is_empty(),
+            DataTypes::Map(map) => !map.borrow().is_empty(),
+            DataTypes::Nil => false,
+            DataTypes::Function(_)
+            | DataTypes::Native(_)
+            | DataTypes::Class(_)
+            | DataTypes::Instance(_) => true,
         }
     }
 }
@@ -52,93 +197,551 @@ impl From<bool> for DataTypes {
 impl From<Option<LiteralType>> for DataTypes {
     fn from(value: Option<LiteralType>) -> Self {
         match value {
-            Some(LiteralType::Number(num)) => DataTypes::Number(num),
-            Some(_) => unimplemented!(),
-            None => unimplemented!(),
+            Some(LiteralType::Integer(num)) => DataTypes::Int(num),
+            Some(LiteralType::BigInteger(num)) => DataTypes::BigInt(num),
+            Some(LiteralType::Float(num)) => DataTypes::Float(num),
+            Some(LiteralType::String(string)) => DataTypes::String(string),
+            None => DataTypes::Nil,
         }
     }
 }
 
 #[derive(Debug)]
-pub enum Expression {
+pub struct Expression {
+    pub kind: ExpressionKind,
+    pub span: Span,
+}
+
+impl Expression {
+    pub fn new(kind: ExpressionKind, span: Span) -> Self {
+        Expression { kind, span }
+    }
+}
+
+#[derive(Debug)]
+pub enum ExpressionKind {
     Binary(Box<Expression>, Token, Box<Expression>),
     Unary(Token, Box<Expression>),
     Literal(Token),
     Grouping(Box<Expression>),
+    Variable(Token),
+    Assign(Token, Box<Expression>),
+    // `target op= value`, the target is a Variable or a Get
+    CompoundAssign(Box<Expression>, Token, Box<Expression>),
+    Logical(Box<Expression>, Token, Box<Expression>),
+    // `condition ? then : otherwise`
+    Conditional(Box<Expression>, Box<Expression>, Box<Expression>),
+    Call(Box<Expression>, Token, Vec<Expression>),
+    Get(Box<Expression>, Token),
+    // `[a, b, c]`
+    List(Vec<Expression>),
+    // `{key: value, ...}`, the token is the opening brace
+    Map(Token, Vec<(Expression, Expression)>),
+    // `object[index]`, the token is the opening bracket
+    Index(Box<Expression>, Token, Box<Expression>),
+    // `object[index] = value`
+    SetIndex(Box<Expression>, Token, Box<Expression>, Box<Expression>),
+    // `object[start:end]`, either bound can be left out
+    Slice(
+        Box<Expression>,
+        Token,
+        Option<Box<Expression>>,
+        Option<Box<Expression>>,
+    ),
+    // `object?.name`, always inside an OptionalChain
+    OptionalGet(Box<Expression>, Token),
+    // a chain of calls, gets and indices holding a `?.`, the whole chain is nil
+    // once a `?.` finds nil
+    OptionalChain(Box<Expression>),
+    Set(Box<Expression>, Token, Box<Expression>),
+    This(Token),
+    Super(Token, Token),
+    // the String pieces and embedded expressions of "a ${b} c", in order
+    Interpolation(Vec<Expression>),
+    // a token the lexer already reported, kept so the tree still lines up with the source
+    Error(Token),
 }
 
 // let var:Option
 
 // visitor pattern for better maintablity
 pub trait ExpressionVistor<T> {
-    fn vist_expr(&self, e: Expression) -> T;
+    fn vist_expr(&mut self, e: &Expression) -> T;
     // fn vist_unary();
     // fn vist_literal();
     // fn vist_grouping();
 }
 
+// a statment stops running either because it failed or because it hit a `return`
+#[derive(Debug)]
+pub enum Unwind {
+    Error(RuntimeError),
+    Return(DataTypes),
+    // the label of the loop to leave or go around again, the innermost one without a label
+    Break(Option<Token>),
+    Continue(Option<Token>),
+}
+
+impl From<RuntimeError> for Unwind {
+    fn from(value: RuntimeError) -> Self {
+        Unwind::Error(value)
+    }
+}
+
 //operations as classes
-pub struct Interpret;
-
-impl ExpressionVistor<DataTypes> for Interpret {
-    //TODO: error handeling
-    fn vist_expr(&self, e: Expression) -> DataTypes {
-        match e {
-            Expression::Binary(exp_a, operator, exp_b) => match operator.token_type {
-                TokenType::MINUS => {
-                    bin_expr::<f32>(self.vist_expr(*exp_a), self.vist_expr(*exp_b), |a, b| a - b)
-                }
+// deeper recursion is reported as an error instead of overflowing the native stack
+const MAX_CALL_DEPTH: usize = 1000;
 
-                TokenType::PLUS => {
-                    bin_expr::<f32>(self.vist_expr(*exp_a), self.vist_expr(*exp_b), |a, b| a + b)
-                }
-                TokenType::SLASH => {
-                    bin_expr::<f32>(self.vist_expr(*exp_a), self.vist_expr(*exp_b), |a, b| a / b)
-                }
-                TokenType::STAR => {
-                    bin_expr::<f32>(self.vist_expr(*exp_a), self.vist_expr(*exp_b), |a, b| a * b)
+pub struct Interpret {
+    environment: Rc<RefCell<Environment>>,
+    lenient: bool,
+    exact: bool,
+    // calls currently running
+    depth: usize,
+}
+
+impl Default for Interpret {
+    fn default() -> Self {
+        let mut globals = Environment::default();
+        for native in callable::natives() {
+            globals.define(
+                native.name.to_string(),
+                Some(DataTypes::Native(Rc::new(native))),
+            );
+        }
+        Interpret {
+            environment: Rc::new(RefCell::new(globals)),
+            lenient: false,
+            exact: false,
+            depth: 0,
+        }
+    }
+}
+
+impl Interpret {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // lenient mode keeps the old behaviour of coercing bools and nil into numbers
+    pub fn lenient(mut self, lenient: bool) -> Self {
+        self.lenient = lenient;
+        self
+    }
+
+    // exact mode turns `/` on two integers into a Rational instead of truncating
+    pub fn exact(mut self, exact: bool) -> Self {
+        self.exact = exact;
+        self
+    }
+
+    pub fn interpret(&mut self, program: &Program) -> Result<(), RuntimeError> {
+        for statment in program {
+            match self.vist_stmt(statment) {
+                Ok(()) => {}
+                Err(Unwind::Error(error)) => return Err(error),
+                // the parser only accepts `return` inside a function body and
+                // `break` and `continue` inside a loop
+                Err(Unwind::Return(_) | Unwind::Break(_) | Unwind::Continue(_)) => unreachable!(),
+            }
+        }
+        Ok(())
+    }
+
+    pub(crate) fn execute_block(
+        &mut self,
+        statments: &[Statment],
+        environment: Environment,
+    ) -> Result<(), Unwind> {
+        let previous = std::mem::replace(&mut self.environment, Rc::new(RefCell::new(environment)));
+        let result = statments
+            .iter()
+            .try_for_each(|statment| self.vist_stmt(statment));
+        self.environment = previous;
+        result
+    }
+}
+
+impl ExpressionVistor<Result<DataTypes, RuntimeError>> for Interpret {
+    fn vist_expr(&mut self, e: &Expression) -> Result<DataTypes, RuntimeError> {
+        match &e.kind {
+            ExpressionKind::Binary(exp_a, operator, exp_b) => {
+                let a = self.vist_expr(exp_a)?;
+                let b = self.vist_expr(exp_b)?;
+                self.binary(a, operator, b)
+            }
+            ExpressionKind::Unary(operator, exp) => {
+                let value = self.vist_expr(exp)?;
+                Ok(match (&operator.token_type, &value) {
+                    (TokenType::MINUS, DataTypes::String(_)) => {
+                        return Err(RuntimeError::new(
+                            operator,
+                            RuntimeErrorKind::InvalidOperand("a Number"),
+                        ));
+                    }
+                    (TokenType::MINUS, _) if self.lenient => {
+                        numeric::negate(&numeric::coerce(&value), operator)?
+                    }
+                    (TokenType::MINUS, _) => numeric::negate(&value, operator)?,
+                    (TokenType::BANG, _) => { !bool::from(value) }.into(),
+                    (TokenType::TILDE, _) if self.lenient => {
+                        numeric::bit_not(&numeric::coerce(&value), operator)?
+                    }
+                    (TokenType::TILDE, _) => numeric::bit_not(&value, operator)?,
+                    _ => {
+                        return Err(RuntimeError::new(
+                            operator,
+                            RuntimeErrorKind::UnsupportedOperator,
+                        ));
+                    }
+                })
+            }
+            ExpressionKind::Literal(token) => match token.token_type {
+                TokenType::NUMBER
+                | TokenType::STRING
+                | TokenType::INTERPOLATION
+                | TokenType::NIL => Ok(token.literal.clone().into()),
+                TokenType::TRUE => Ok(DataTypes::Bool(true)),
+                TokenType::FALSE => Ok(DataTypes::Bool(false)),
+                _ => Err(RuntimeError::new(
+                    token,
+                    RuntimeErrorKind::UnsupportedLiteral,
+                )),
+            },
+
+            ExpressionKind::Grouping(exp) => self.vist_expr(exp),
+            ExpressionKind::Variable(name) => self.environment.borrow().get(name),
+            ExpressionKind::Assign(name, exp) => {
+                let value = self.vist_expr(exp)?;
+                self.environment.borrow_mut().assign(name, value.clone())?;
+                Ok(value)
+            }
+            ExpressionKind::CompoundAssign(target, operator, exp) => {
+                // runs as `target = target op value` with the target only evaluated once,
+                // the operator keeps its `op=` text for error messages
+                let token_type = match operator.token_type {
+                    TokenType::PlusEqual => TokenType::PLUS,
+                    TokenType::MinusEqual => TokenType::MINUS,
+                    TokenType::StarEqual => TokenType::STAR,
+                    _ => TokenType::SLASH,
+                };
+                let binary = Token::new(token_type, operator.text.clone(), None, operator.span);
+                match &target.kind {
+                    ExpressionKind::Variable(name) => {
+                        let current = self.environment.borrow().get(name)?;
+                        let value = self.vist_expr(exp)?;
+                        let value = self.binary(current, &binary, value)?;
+                        self.environment.borrow_mut().assign(name, value.clone())?;
+                        Ok(value)
+                    }
+                    ExpressionKind::Get(object, name) => match self.vist_expr(object)? {
+                        DataTypes::Instance(instance) => {
+                            let current = Instance::get(&instance, name)?;
+                            let value = self.vist_expr(exp)?;
+                            let value = self.binary(current, &binary, value)?;
+                            instance.borrow_mut().set(name, value.clone());
+                            Ok(value)
+                        }
+                        _ => Err(RuntimeError::new(name, RuntimeErrorKind::NotAnInstance)),
+                    },
+                    ExpressionKind::Index(object, bracket, index) => {
+                        let object = self.vist_expr(object)?;
+                        let index = self.vist_expr(index)?;
+                        let current = get_index(&object, bracket, &index)?;
+                        let value = self.vist_expr(exp)?;
+                        let value = self.binary(current, &binary, value)?;
+                        set_index(&object, bracket, &index, value.clone())?;
+                        Ok(value)
+                    }
+                    _ => unreachable!("the parser only allows variables, properties and indices"),
                 }
-                TokenType::EqualEqual => {
-                    bin_expr::<bool>(self.vist_expr(*exp_a), self.vist_expr(*exp_b), |a, b| {
-                        a == b
-                    })
+            }
+            ExpressionKind::Logical(exp_a, operator, exp_b) => {
+                let a = self.vist_expr(exp_a)?;
+                // the left operand decides the result unless it is inconclusive
+                match (&operator.token_type, bool::from(a.clone())) {
+                    (TokenType::QuestionQuestion, _) if a != DataTypes::Nil => Ok(a),
+                    (TokenType::QuestionQuestion, _) => self.vist_expr(exp_b),
+                    (TokenType::OR, true) | (TokenType::AND, false) => Ok(a),
+                    _ => self.vist_expr(exp_b),
                 }
-                TokenType::BangEqual => {
-                    bin_expr::<bool>(self.vist_expr(*exp_a), self.vist_expr(*exp_b), |a, b| {
-                        a != b
-                    })
+            }
+            ExpressionKind::Conditional(condition, then, otherwise) => {
+                match bool::from(self.vist_expr(condition)?) {
+                    true => self.vist_expr(then),
+                    false => self.vist_expr(otherwise),
                 }
-                TokenType::LessEqual => {
-                    bin_expr::<bool>(self.vist_expr(*exp_a), self.vist_expr(*exp_b), |a, b| {
-                        a <= b
-                    })
+            }
+            ExpressionKind::Call(callee, paren, arguments) => {
+                let callee = self.vist_expr(callee)?;
+                self.call(callee, paren, arguments)
+            }
+            ExpressionKind::Get(object, name) => get_property(self.vist_expr(object)?, name),
+            ExpressionKind::OptionalGet(..) | ExpressionKind::OptionalChain(_) => {
+                Ok(self.chain(e)?.unwrap_or(DataTypes::Nil))
+            }
+            ExpressionKind::Set(object, name, exp) => match self.vist_expr(object)? {
+                DataTypes::Instance(instance) => {
+                    let value = self.vist_expr(exp)?;
+                    instance.borrow_mut().set(name, value.clone());
+                    Ok(value)
                 }
-                TokenType::LESS => {
-                    bin_expr::<bool>(self.vist_expr(*exp_a), self.vist_expr(*exp_b), |a, b| a < b)
+                _ => Err(RuntimeError::new(name, RuntimeErrorKind::NotAnInstance)),
+            },
+            ExpressionKind::This(keyword) => self.environment.borrow().get(keyword),
+            ExpressionKind::Super(keyword, method) => {
+                let superclass = self.environment.borrow().get(keyword)?;
+                let this = Token::new(TokenType::THIS, "this".to_string(), None, keyword.span);
+                let object = self.environment.borrow().get(&this)?;
+                let DataTypes::Class(superclass) = superclass else {
+                    unreachable!("super is only ever bound to a class")
+                };
+                match superclass.find_method(&method.text) {
+                    Some(function) => Ok(DataTypes::Function(Rc::new(function.bind(object)))),
+                    None => Err(RuntimeError::new(
+                        method,
+                        RuntimeErrorKind::UndefinedProperty,
+                    )),
                 }
-                TokenType::GreaterEqual => {
-                    bin_expr::<bool>(self.vist_expr(*exp_a), self.vist_expr(*exp_b), |a, b| {
-                        a >= b
-                    })
+            }
+            ExpressionKind::List(elements) => {
+                let list = elements
+                    .iter()
+                    .map(|element| self.vist_expr(element))
+                    .collect::<Result<Vec<DataTypes>, RuntimeError>>()?;
+                Ok(DataTypes::List(Rc::new(RefCell::new(list))))
+            }
+            ExpressionKind::Map(brace, entries) => {
+                let mut map = Map::new();
+                for (key, value) in entries {
+                    let key = map_key(brace, &self.vist_expr(key)?)?;
+                    map.insert(key, self.vist_expr(value)?);
                 }
-                TokenType::GREATER => {
-                    bin_expr::<bool>(self.vist_expr(*exp_a), self.vist_expr(*exp_b), |a, b| a > b)
+                Ok(DataTypes::Map(Rc::new(RefCell::new(map))))
+            }
+            ExpressionKind::Index(object, bracket, index) => {
+                let object = self.vist_expr(object)?;
+                let index = self.vist_expr(index)?;
+                get_index(&object, bracket, &index)
+            }
+            ExpressionKind::SetIndex(object, bracket, index, exp) => {
+                let object = self.vist_expr(object)?;
+                let index = self.vist_expr(index)?;
+                let value = self.vist_expr(exp)?;
+                set_index(&object, bracket, &index, value.clone())?;
+                Ok(value)
+            }
+            ExpressionKind::Slice(object, bracket, start, end) => {
+                let object = self.vist_expr(object)?;
+                self.slice(object, bracket, start, end)
+            }
+            ExpressionKind::Interpolation(parts) => {
+                let mut string = String::new();
+                for part in parts {
+                    string.push_str(&self.vist_expr(part)?.to_string());
                 }
+                Ok(DataTypes::String(string))
+            }
+            ExpressionKind::Error(token) => {
+                Err(RuntimeError::new(token, RuntimeErrorKind::InvalidSyntax))
+            }
+        }
+    }
+}
 
-                _ => unimplemented!(),
-            },
-            Expression::Unary(operator, exp) => match operator.token_type {
-                TokenType::MINUS => { -f32::from(self.vist_expr(*exp)) }.into(),
-                TokenType::BANG => { !bool::from(self.vist_expr(*exp)) }.into(),
-                _ => unimplemented!(),
-            },
-            Expression::Literal(token) => match token.token_type {
-                TokenType::NUMBER => token.literal.into(),
-                _ => unimplemented!(),
+impl Interpret {
+    fn call(
+        &mut self,
+        callee: DataTypes,
+        paren: &Token,
+        arguments: &[Expression],
+    ) -> Result<DataTypes, RuntimeError> {
+        let arguments = arguments
+            .iter()
+            .map(|argument| self.vist_expr(argument))
+            .collect::<Result<Vec<DataTypes>, RuntimeError>>()?;
+
+        let callee: &dyn Callable = match &callee {
+            DataTypes::Function(function) => function.as_ref(),
+            DataTypes::Native(native) => native.as_ref(),
+            DataTypes::Class(class) => class,
+            _ => return Err(RuntimeError::new(paren, RuntimeErrorKind::NotCallable)),
+        };
+        if arguments.len() != callee.arity() {
+            return Err(RuntimeError::new(
+                paren,
+                RuntimeErrorKind::ArityMismatch(callee.arity(), arguments.len()),
+            ));
+        }
+        if self.depth >= MAX_CALL_DEPTH {
+            return Err(RuntimeError::new(paren, RuntimeErrorKind::StackOverflow));
+        }
+        self.depth += 1;
+        let result = callee.call(self, paren, arguments);
+        self.depth -= 1;
+        result
+    }
+
+    fn slice(
+        &mut self,
+        object: DataTypes,
+        bracket: &Token,
+        start: &Option<Box<Expression>>,
+        end: &Option<Box<Expression>>,
+    ) -> Result<DataTypes, RuntimeError> {
+        let DataTypes::List(list) = object else {
+            return Err(RuntimeError::new(bracket, RuntimeErrorKind::NotSliceable));
+        };
+        let start = start
+            .as_ref()
+            .map(|start| self.vist_expr(start))
+            .transpose()?;
+        let end = end.as_ref().map(|end| self.vist_expr(end)).transpose()?;
+        let list = list.borrow();
+        let start = slice_bound(bracket, start, list.len(), 0)?;
+        let end = slice_bound(bracket, end, list.len(), list.len())?;
+        let slice = list[start..end.max(start)].to_vec();
+        Ok(DataTypes::List(Rc::new(RefCell::new(slice))))
+    }
+
+    // evaluates the links of an OptionalChain, None once a `?.` finds nil
+    fn chain(&mut self, e: &Expression) -> Result<Option<DataTypes>, RuntimeError> {
+        let link = match &e.kind {
+            ExpressionKind::OptionalChain(chain) => return self.chain(chain),
+            ExpressionKind::OptionalGet(object, name) => match self.chain(object)? {
+                None | Some(DataTypes::Nil) => return Ok(None),
+                Some(object) => get_property(object, name)?,
             },
+            ExpressionKind::Get(object, name) => {
+                let Some(object) = self.chain(object)? else {
+                    return Ok(None);
+                };
+                get_property(object, name)?
+            }
+            ExpressionKind::Call(callee, paren, arguments) => {
+                let Some(callee) = self.chain(callee)? else {
+                    return Ok(None);
+                };
+                self.call(callee, paren, arguments)?
+            }
+            ExpressionKind::Index(object, bracket, index) => {
+                let Some(object) = self.chain(object)? else {
+                    return Ok(None);
+                };
+                let index = self.vist_expr(index)?;
+                get_index(&object, bracket, &index)?
+            }
+            ExpressionKind::Slice(object, bracket, start, end) => {
+                let Some(object) = self.chain(object)? else {
+                    return Ok(None);
+                };
+                self.slice(object, bracket, start, end)?
+            }
+            _ => self.vist_expr(e)?,
+        };
+        Ok(Some(link))
+    }
+
+    fn binary(
+        &self,
+        a: DataTypes,
+        operator: &Token,
+        b: DataTypes,
+    ) -> Result<DataTypes, RuntimeError> {
+        match self.lenient {
+            true => lenient_expr(a, operator, b, self.exact),
+            false => strict_expr(&a, operator, &b, self.exact),
+        }
+    }
+}
+
+// negative indices count back from the end of the List
+fn list_index(bracket: &Token, index: &DataTypes, len: usize) -> Result<usize, RuntimeError> {
+    let DataTypes::Int(index) = *index else {
+        return Err(RuntimeError::new(bracket, RuntimeErrorKind::InvalidIndex));
+    };
+    let position = match index < 0 {
+        true => index + len as i64,
+        false => index,
+    };
+    if position < 0 || position >= len as i64 {
+        return Err(RuntimeError::new(
+            bracket,
+            RuntimeErrorKind::IndexOutOfBounds(index, len),
+        ));
+    }
+    Ok(position as usize)
+}
+
+fn get_property(object: DataTypes, name: &Token) -> Result<DataTypes, RuntimeError> {
+    match object {
+        DataTypes::Instance(instance) => Instance::get(&instance, name),
+        _ => Err(RuntimeError::new(name, RuntimeErrorKind::NotAnInstance)),
+    }
+}
+
+fn get_index(
+    object: &DataTypes,
+    bracket: &Token,
+    index: &DataTypes,
+) -> Result<DataTypes, RuntimeError> {
+    match object {
+        DataTypes::List(list) => {
+            let list = list.borrow();
+            Ok(list[list_index(bracket, index, list.len())?].clone())
+        }
+        DataTypes::Map(map) => {
+            let key = map_key(bracket, index)?;
+            match map.borrow().get(&key) {
+                Some(value) => Ok(value.clone()),
+                None => Err(RuntimeError::new(
+                    bracket,
+                    RuntimeErrorKind::MissingKey(key.to_string()),
+                )),
+            }
+        }
+        _ => Err(RuntimeError::new(bracket, RuntimeErrorKind::NotIndexable)),
+    }
+}
 
-            Expression::Grouping(exp) => self.vist_expr(*exp),
+fn set_index(
+    object: &DataTypes,
+    bracket: &Token,
+    index: &DataTypes,
+    value: DataTypes,
+) -> Result<(), RuntimeError> {
+    match object {
+        DataTypes::List(list) => {
+            let mut list = list.borrow_mut();
+            let position = list_index(bracket, index, list.len())?;
+            list[position] = value;
+            Ok(())
         }
+        DataTypes::Map(map) => {
+            map.borrow_mut().insert(map_key(bracket, index)?, value);
+            Ok(())
+        }
+        _ => Err(RuntimeError::new(bracket, RuntimeErrorKind::NotIndexable)),
+    }
+}
+
+pub(crate) fn map_key(token: &Token, key: &DataTypes) -> Result<Key, RuntimeError> {
+    Key::new(key).ok_or_else(|| RuntimeError::new(token, RuntimeErrorKind::UnhashableKey))
+}
+
+// unlike an index a slice bound past either end is clamped to it, a missing
+// or nil bound takes the default
+fn slice_bound(
+    bracket: &Token,
+    bound: Option<DataTypes>,
+    len: usize,
+    default: usize,
+) -> Result<usize, RuntimeError> {
+    match bound {
+        None | Some(DataTypes::Nil) => Ok(default),
+        Some(DataTypes::Int(bound)) if bound < 0 => Ok((bound + len as i64).max(0) as usize),
+        Some(DataTypes::Int(bound)) => Ok((bound as u64).min(len as u64) as usize),
+        Some(_) => Err(RuntimeError::new(bracket, RuntimeErrorKind::InvalidIndex)),
     }
 }
 
@@ -151,75 +754,908 @@ where
     fun(D::from(exp_a), D::from(exp_b)).into()
 }
 
+// every operator checks the types of its operands, `==` compares values and
+// is always false between two different types
+fn strict_expr(
+    a: &DataTypes,
+    operator: &Token,
+    b: &DataTypes,
+    exact: bool,
+) -> Result<DataTypes, RuntimeError> {
+    match (&operator.token_type, a, b) {
+        (TokenType::EqualEqual, ..) => Ok((a == b).into()),
+        (TokenType::BangEqual, ..) => Ok((a != b).into()),
+        (_, a, b) if numeric::is_number(a) && numeric::is_number(b) => {
+            numeric::number_expr(a, operator, b, exact)
+        }
+        (TokenType::PLUS, DataTypes::String(a), DataTypes::String(b)) => {
+            Ok(DataTypes::String(format!("{a}{b}")))
+        }
+        (TokenType::LESS, DataTypes::String(a), DataTypes::String(b)) => Ok((a < b).into()),
+        (TokenType::LessEqual, DataTypes::String(a), DataTypes::String(b)) => Ok((a <= b).into()),
+        (TokenType::GREATER, DataTypes::String(a), DataTypes::String(b)) => Ok((a > b).into()),
+        (TokenType::GreaterEqual, DataTypes::String(a), DataTypes::String(b)) => {
+            Ok((a >= b).into())
+        }
+        (
+            TokenType::PLUS
+            | TokenType::LESS
+            | TokenType::LessEqual
+            | TokenType::GREATER
+            | TokenType::GreaterEqual,
+            ..,
+        ) => Err(RuntimeError::new(
+            operator,
+            RuntimeErrorKind::InvalidOperands("two Numbers or two Strings"),
+        )),
+        (
+            TokenType::MINUS
+            | TokenType::STAR
+            | TokenType::SLASH
+            | TokenType::SlashSlash
+            | TokenType::PERCENT
+            | TokenType::StarStar,
+            ..,
+        ) => Err(RuntimeError::new(
+            operator,
+            RuntimeErrorKind::InvalidOperands("Numbers"),
+        )),
+        (
+            TokenType::AMPERSAND
+            | TokenType::PIPE
+            | TokenType::CARET
+            | TokenType::LessLess
+            | TokenType::GreaterGreater,
+            ..,
+        ) => Err(RuntimeError::new(
+            operator,
+            RuntimeErrorKind::InvalidOperands("Ints"),
+        )),
+        _ => Err(RuntimeError::new(
+            operator,
+            RuntimeErrorKind::UnsupportedOperator,
+        )),
+    }
+}
+
+// the older rules, kept behind the lenient mode: numbers, bools and nil are
+// coerced into each other, strings still follow the strict rules
+fn lenient_expr(
+    a: DataTypes,
+    operator: &Token,
+    b: DataTypes,
+    exact: bool,
+) -> Result<DataTypes, RuntimeError> {
+    let is_equality = [TokenType::EqualEqual, TokenType::BangEqual].contains(&operator.token_type);
+    match (&a, &b) {
+        (DataTypes::String(_), _) | (_, DataTypes::String(_)) => {
+            return strict_expr(&a, operator, &b, exact);
+        }
+        (DataTypes::Nil, _) | (_, DataTypes::Nil) if is_equality => {
+            return strict_expr(&a, operator, &b, exact);
+        }
+        _ => {}
+    }
+    match operator.token_type {
+        TokenType::EqualEqual => Ok(bin_expr::<bool>(a, b, |a, b| a == b)),
+        TokenType::BangEqual => Ok(bin_expr::<bool>(a, b, |a, b| a != b)),
+        _ => numeric::number_expr(&numeric::coerce(&a), operator, &numeric::coerce(&b), exact),
+    }
+}
+
 pub struct PrettyPrint;
 impl ExpressionVistor<String> for PrettyPrint {
-    fn vist_expr(&self, e: Expression) -> String {
-        match e {
-            Expression::Binary(exp1, op, exp2) => {
+    fn vist_expr(&mut self, e: &Expression) -> String {
+        match &e.kind {
+            ExpressionKind::Binary(exp1, op, exp2) => {
                 format!(
                     "({} {} {})",
                     op.text,
-                    self.vist_expr(*exp1),
-                    self.vist_expr(*exp2)
+                    self.vist_expr(exp1),
+                    self.vist_expr(exp2)
                 )
             }
-            Expression::Unary(op, exp) => {
-                format!("({} {})", op.text, self.vist_expr(*exp))
+            ExpressionKind::Unary(op, exp) => {
+                format!("({} {})", op.text, self.vist_expr(exp))
+            }
+            ExpressionKind::Literal(token) => token
+                .literal
+                .as_ref()
+                .map_or(token.text.clone(), LiteralType::print),
+            ExpressionKind::Grouping(exp) => format!("(group {})", self.vist_expr(exp)),
+            ExpressionKind::Variable(name) => name.text.clone(),
+            ExpressionKind::Assign(name, exp) => {
+                format!("(= {} {})", name.text, self.vist_expr(exp))
+            }
+            ExpressionKind::CompoundAssign(target, op, exp) => {
+                format!(
+                    "({} {} {})",
+                    op.text,
+                    self.vist_expr(target),
+                    self.vist_expr(exp)
+                )
+            }
+            ExpressionKind::Logical(exp1, op, exp2) => {
+                format!(
+                    "({} {} {})",
+                    op.text,
+                    self.vist_expr(exp1),
+                    self.vist_expr(exp2)
+                )
+            }
+            ExpressionKind::Conditional(condition, then, otherwise) => format!(
+                "(?: {} {} {})",
+                self.vist_expr(condition),
+                self.vist_expr(then),
+                self.vist_expr(otherwise)
+            ),
+            ExpressionKind::Call(callee, _, arguments) => {
+                let mut text = format!("(call {}", self.vist_expr(callee));
+                for argument in arguments {
+                    text.push(' ');
+                    text.push_str(&self.vist_expr(argument));
+                }
+                text.push(')');
+                text
+            }
+            ExpressionKind::Get(object, name) => {
+                format!("(. {} {})", self.vist_expr(object), name.text)
+            }
+            ExpressionKind::OptionalGet(object, name) => {
+                format!("(?. {} {})", self.vist_expr(object), name.text)
             }
-            Expression::Literal(token) => format!(
-                "{}",
-                token
-                    .literal
-                    .expect("INTERNAL ERROR: no literal is None")
-                    .print()
+            ExpressionKind::OptionalChain(chain) => self.vist_expr(chain),
+            ExpressionKind::List(elements) => format!(
+                "(list {})",
+                elements
+                    .iter()
+                    .map(|element| self.vist_expr(element))
+                    .collect::<Vec<String>>()
+                    .join(" ")
             ),
-            Expression::Grouping(exp) => format!("(group {})", self.vist_expr(*exp)),
+            ExpressionKind::Map(_, entries) => format!(
+                "(map {})",
+                entries
+                    .iter()
+                    .map(|(key, value)| format!(
+                        "({} {})",
+                        self.vist_expr(key),
+                        self.vist_expr(value)
+                    ))
+                    .collect::<Vec<String>>()
+                    .join(" ")
+            ),
+            ExpressionKind::Index(object, _, index) => {
+                format!("([] {} {})", self.vist_expr(object), self.vist_expr(index))
+            }
+            ExpressionKind::SetIndex(object, _, index, exp) => format!(
+                "(= ([] {} {}) {})",
+                self.vist_expr(object),
+                self.vist_expr(index),
+                self.vist_expr(exp)
+            ),
+            ExpressionKind::Slice(object, _, start, end) => format!(
+                "([:] {} {} {})",
+                self.vist_expr(object),
+                start
+                    .as_ref()
+                    .map_or("nil".to_string(), |start| self.vist_expr(start)),
+                end.as_ref()
+                    .map_or("nil".to_string(), |end| self.vist_expr(end))
+            ),
+            ExpressionKind::Set(object, name, exp) => format!(
+                "(= (. {} {}) {})",
+                self.vist_expr(object),
+                name.text,
+                self.vist_expr(exp)
+            ),
+            ExpressionKind::This(_) => "this".to_string(),
+            ExpressionKind::Super(_, method) => format!("(super {})", method.text),
+            ExpressionKind::Interpolation(parts) => format!(
+                "(interpolate {})",
+                parts
+                    .iter()
+                    .map(|part| self.vist_expr(part))
+                    .collect::<Vec<String>>()
+                    .join(" ")
+            ),
+            ExpressionKind::Error(token) => format!("(error {})", token.text),
         }
     }
 }
 
-enum Statment {
+#[derive(Debug)]
+pub enum Statment {
     ExpresisonStatment(Expression),
     PrintStatment(Expression),
+    LetStatment(Token, Option<Expression>),
+    BlockStatment(Vec<Statment>),
+    IfStatment(Expression, Box<Statment>, Option<Box<Statment>>),
+    // the increment of a desugared `for` runs after the body, even on `continue`
+    WhileStatment(
+        Expression,
+        Box<Statment>,
+        Option<Box<Expression>>,
+        Option<Token>,
+    ),
+    BreakStatment(Token, Option<Token>),
+    ContinueStatment(Token, Option<Token>),
+    FunctionStatment(Rc<FunctionDeclaration>),
+    ReturnStatment(Token, Option<Expression>),
+    ClassStatment(Token, Option<Expression>, Vec<Rc<FunctionDeclaration>>),
+    // a statment that failed to parse, holding the token it started at
+    ErrorStatment(Token),
 }
 
-pub struct Parser {
-    tokens: Peekable<IntoIter<Token>>,
+// shared between the syntax tree and every function value created from it
+#[derive(Debug)]
+pub struct FunctionDeclaration {
+    pub name: Token,
+    pub params: Vec<Token>,
+    pub body: Vec<Statment>,
+}
+
+/// A whole source file: the statments in the order they appear.
+pub type Program = Vec<Statment>;
+
+pub trait StatmentVistor<T> {
+    fn vist_stmt(&mut self, s: &Statment) -> T;
 }
 
-impl Parser {
-    pub fn new(tokens: Vec<Token>) -> Self {
+impl StatmentVistor<Result<(), Unwind>> for Interpret {
+    fn vist_stmt(&mut self, s: &Statment) -> Result<(), Unwind> {
+        match s {
+            Statment::ExpresisonStatment(exp) => {
+                self.vist_expr(exp)?;
+            }
+            Statment::PrintStatment(exp) => println!("{}", self.vist_expr(exp)?),
+            Statment::LetStatment(name, initializer) => {
+                let value = initializer
+                    .as_ref()
+                    .map(|exp| self.vist_expr(exp))
+                    .transpose()?;
+                self.environment
+                    .borrow_mut()
+                    .define(name.text.clone(), value);
+            }
+            Statment::BlockStatment(statments) => {
+                let environment = Environment::new(Rc::clone(&self.environment));
+                self.execute_block(statments, environment)?;
+            }
+            Statment::IfStatment(condition, then_branch, else_branch) => {
+                if bool::from(self.vist_expr(condition)?) {
+                    self.vist_stmt(then_branch)?;
+                } else if let Some(else_branch) = else_branch {
+                    self.vist_stmt(else_branch)?;
+                }
+            }
+            Statment::WhileStatment(condition, body, increment, label) => {
+                // a jump without a label is for the innermost loop
+                let is_target = |target: &Option<Token>| match (target, label) {
+                    (None, _) => true,
+                    (Some(target), Some(label)) => target.text == label.text,
+                    (Some(_), None) => false,
+                };
+                while bool::from(self.vist_expr(condition)?) {
+                    match self.vist_stmt(body) {
+                        Ok(()) => {}
+                        Err(Unwind::Break(target)) if is_target(&target) => break,
+                        Err(Unwind::Continue(target)) if is_target(&target) => {}
+                        Err(unwind) => return Err(unwind),
+                    }
+                    if let Some(increment) = increment {
+                        self.vist_expr(increment)?;
+                    }
+                }
+            }
+            Statment::BreakStatment(_, label) => return Err(Unwind::Break(label.clone())),
+            Statment::ContinueStatment(_, label) => return Err(Unwind::Continue(label.clone())),
+            Statment::FunctionStatment(declaration) => {
+                let function =
+                    Function::new(Rc::clone(declaration), Rc::clone(&self.environment), false);
+                self.environment.borrow_mut().define(
+                    declaration.name.text.clone(),
+                    Some(DataTypes::Function(Rc::new(function))),
+                );
+            }
+            Statment::ReturnStatment(_, value) => {
+                let value = match value {
+                    Some(exp) => self.vist_expr(exp)?,
+                    None => DataTypes::Nil,
+                };
+                return Err(Unwind::Return(value));
+            }
+            Statment::ClassStatment(name, superclass, methods) => {
+                let superclass = match superclass {
+                    Some(exp) => match self.vist_expr(exp)? {
+                        DataTypes::Class(class) => Some(class),
+                        _ => {
+                            return Err(RuntimeError::new(
+                                name,
+                                RuntimeErrorKind::SuperclassNotClass,
+                            )
+                            .into());
+                        }
+                    },
+                    None => None,
+                };
+                self.environment
+                    .borrow_mut()
+                    .define(name.text.clone(), None);
+
+                // methods of a subclass close over a scope that holds `super`
+                let enclosing = Rc::clone(&self.environment);
+                if let Some(superclass) = &superclass {
+                    let mut environment = Environment::new(Rc::clone(&self.environment));
+                    environment.define(
+                        "super".to_string(),
+                        Some(DataTypes::Class(Rc::clone(superclass))),
+                    );
+                    self.environment = Rc::new(RefCell::new(environment));
+                }
+
+                let methods = methods
+                    .iter()
+                    .map(|method| {
+                        let function = Function::new(
+                            Rc::clone(method),
+                            Rc::clone(&self.environment),
+                            method.name.text == "init",
+                        );
+                        (method.name.text.clone(), Rc::new(function))
+                    })
+                    .collect::<HashMap<String, Rc<Function>>>();
+
+                self.environment = enclosing;
+                let class = Class::new(name.text.clone(), superclass, methods);
+                self.environment
+                    .borrow_mut()
+                    .assign(name, DataTypes::Class(Rc::new(class)))?;
+            }
+            Statment::ErrorStatment(token) => {
+                return Err(RuntimeError::new(token, RuntimeErrorKind::InvalidSyntax).into());
+            }
+        }
+        Ok(())
+    }
+}
+
+// what kind of body the parser is in, used to reject misplaced `return`, `this` and `super`
+#[derive(Clone, Copy, PartialEq)]
+enum FunctionKind {
+    None,
+    Function,
+    Method,
+    Initializer,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum ClassKind {
+    None,
+    Class,
+    Subclass,
+}
+
+// reads from any source of tokens, a Vec or a Lexer streaming them
+pub struct Parser<I: Iterator<Item = Token>> {
+    tokens: Peekable<I>,
+    function_kind: FunctionKind,
+    class_kind: ClassKind,
+    // the labels of the loops around the current statment, innermost last
+    loops: Vec<Option<Token>>,
+    // the span of the last token taken and how many have been taken
+    previous: Span,
+    consumed: usize,
+    // how many of the `{` taken are still open
+    braces: usize,
+    error_set: ErrorSet,
+}
+
+impl<I: Iterator<Item = Token>> Parser<I> {
+    pub fn new(tokens: impl IntoIterator<Item = Token, IntoIter = I>) -> Self {
         Parser {
             tokens: tokens.into_iter().peekable(),
+            function_kind: FunctionKind::None,
+            class_kind: ClassKind::None,
+            loops: Vec::new(),
+            previous: Span {
+                start_line: 1,
+                start_column: 1,
+                end_line: 1,
+                end_column: 1,
+                ..Span::default()
+            },
+            consumed: 0,
+            braces: 0,
+            error_set: ErrorSet::default(),
         }
     }
-    pub fn parse(&mut self) -> Vec<Statment> {
-        let mut statemets: Vec<Statment> = Vec::new();
-        while let Some(token) = self.tokens.next() {
-            statemets.push();
+
+    // parses as much as it can, statments that fail become ErrorStatment
+    pub fn parse(&mut self) -> (Program, ErrorSet) {
+        let mut statments: Program = Vec::new();
+        while !self.is_at_end() {
+            // a `}` left open by a statment that failed to parse has no block to close
+            if self.braces > 0 && self.check(TokenType::RightBrace) {
+                self.next_if(|_| true);
+                continue;
+            }
+            statments.push(self.declaration());
         }
-        return statemets;
+        (statments, std::mem::take(&mut self.error_set))
     }
-    fn expression(&mut self) -> Result<Expression, Errors> {
-        return Ok(self.eqality()?);
+
+    fn declaration(&mut self) -> Statment {
+        let start = self.found();
+        let consumed = self.consumed;
+        match self.try_declaration() {
+            Ok(statment) => statment,
+            Err(error) => {
+                // the lexer has already reported its ERROR tokens
+                let reported = error
+                    .token()
+                    .is_some_and(|token| token.token_type == TokenType::ERROR);
+                if !reported {
+                    self.error_set.push(error);
+                }
+                self.synchronize();
+                // a stray `}` is not consumed by anything, skip it so parsing moves on
+                if self.consumed == consumed {
+                    self.next_if(|token| token.token_type != TokenType::EOF);
+                }
+                Statment::ErrorStatment(*start)
+            }
+        }
+    }
+
+    // skip ahead to where the next statment probably starts, a `}` is left for
+    // the enclosing block to close on
+    fn synchronize(&mut self) {
+        while let Some(token) = self.tokens.peek() {
+            match token.token_type {
+                TokenType::EOF
+                | TokenType::RightBrace
+                | TokenType::FUN
+                | TokenType::LET
+                | TokenType::FOR
+                | TokenType::IF
+                | TokenType::WHILE
+                | TokenType::PRINT
+                | TokenType::RETURN
+                | TokenType::BREAK
+                | TokenType::CONTINUE
+                | TokenType::CLASS => return,
+                TokenType::SEMICOLON => {
+                    self.next_if(|_| true);
+                    return;
+                }
+                _ => {
+                    self.next_if(|_| true);
+                }
+            }
+        }
+    }
+
+    fn try_declaration(&mut self) -> Result<Statment, Errors> {
+        if self.match_token(TokenType::LET).is_some() {
+            return self.let_declaration();
+        }
+        if self.match_token(TokenType::FUN).is_some() {
+            return Ok(Statment::FunctionStatment(Rc::new(
+                self.function(FunctionKind::Function)?,
+            )));
+        }
+        if self.match_token(TokenType::CLASS).is_some() {
+            return self.class_declaration();
+        }
+        self.statment()
+    }
+
+    fn class_declaration(&mut self) -> Result<Statment, Errors> {
+        let name = self.consume(TokenType::IDENTIFIER, Errors::MissingClassName)?;
+
+        let superclass = match self.match_token(TokenType::LESS) {
+            Some(_) => {
+                let superclass =
+                    self.consume(TokenType::IDENTIFIER, Errors::MissingSuperclassName)?;
+                if superclass.text == name.text {
+                    return Err(Errors::InheritFromSelf(Box::new(superclass)));
+                }
+                let span = superclass.span;
+                Some(Expression::new(ExpressionKind::Variable(superclass), span))
+            }
+            None => None,
+        };
+        let open = self.consume(TokenType::LeftBrace, Errors::MissingClassBody)?;
+
+        let enclosing = self.class_kind;
+        self.class_kind = match superclass {
+            Some(_) => ClassKind::Subclass,
+            None => ClassKind::Class,
+        };
+        let methods = self.methods(open.span);
+        self.class_kind = enclosing;
+
+        Ok(Statment::ClassStatment(name, superclass, methods?))
+    }
+
+    fn methods(&mut self, open: Span) -> Result<Vec<Rc<FunctionDeclaration>>, Errors> {
+        let mut methods = Vec::new();
+        while !self.check(TokenType::RightBrace) && !self.is_at_end() {
+            let kind = match self.tokens.peek() {
+                Some(token) if token.text == "init" => FunctionKind::Initializer,
+                _ => FunctionKind::Method,
+            };
+            methods.push(Rc::new(self.function(kind)?));
+        }
+        self.consume(TokenType::RightBrace, |found| {
+            Errors::UntermitedBlock(found, open)
+        })?;
+        Ok(methods)
+    }
+
+    fn function(&mut self, kind: FunctionKind) -> Result<FunctionDeclaration, Errors> {
+        let name = self.consume(TokenType::IDENTIFIER, Errors::MissingFunctionName)?;
+        let open = self.consume(TokenType::LeftPara, Errors::MissingParameterList)?;
+        let mut params = Vec::new();
+        if !self.check(TokenType::RightPara) {
+            loop {
+                params.push(self.consume(TokenType::IDENTIFIER, Errors::MissingParameterName)?);
+                if self.match_token(TokenType::COMMA).is_none() {
+                    break;
+                }
+            }
+        }
+        self.consume(TokenType::RightPara, |found| {
+            Errors::UntermitedParameterList(found, open.span)
+        })?;
+        let open = self.consume(TokenType::LeftBrace, Errors::MissingFunctionBody)?;
+
+        // loops outside the function can not be jumped out of from inside it
+        let enclosing = self.function_kind;
+        let loops = std::mem::take(&mut self.loops);
+        self.function_kind = kind;
+        let body = self.block(open.span);
+        self.function_kind = enclosing;
+        self.loops = loops;
+
+        Ok(FunctionDeclaration {
+            name,
+            params,
+            body: body?,
+        })
+    }
+
+    fn let_declaration(&mut self) -> Result<Statment, Errors> {
+        let name = self.consume(TokenType::IDENTIFIER, Errors::MissingVariableName)?;
+        let initializer = match self.match_token(TokenType::EQUAL) {
+            Some(_) => Some(self.expression()?),
+            None => None,
+        };
+        self.consume(TokenType::SEMICOLON, Errors::MissingSemicolon)?;
+        Ok(Statment::LetStatment(name, initializer))
+    }
+
+    fn statment(&mut self) -> Result<Statment, Errors> {
+        if self.match_token(TokenType::PRINT).is_some() {
+            return self.print_statment();
+        }
+        if let Some(open) = self.match_token(TokenType::LeftBrace) {
+            return Ok(Statment::BlockStatment(self.block(open.span)?));
+        }
+        if self.match_token(TokenType::IF).is_some() {
+            return self.if_statment();
+        }
+        if self.match_token(TokenType::WHILE).is_some() {
+            return self.while_statment(None);
+        }
+        if let Some(keyword) = self.match_token(TokenType::FOR) {
+            return self.for_statment(keyword, None);
+        }
+        if let Some(label) = self.match_token(TokenType::LABEL) {
+            return self.labelled_statment(label);
+        }
+        if let Some(keyword) = self.match_token(TokenType::RETURN) {
+            return self.return_statment(keyword);
+        }
+        if let Some(keyword) = self
+            .next_if(|token| [TokenType::BREAK, TokenType::CONTINUE].contains(&token.token_type))
+        {
+            return self.jump_statment(keyword);
+        }
+        self.expression_statment()
+    }
+
+    fn return_statment(&mut self, keyword: Token) -> Result<Statment, Errors> {
+        if self.function_kind == FunctionKind::None {
+            return Err(Errors::ReturnOutsideFunction(Box::new(keyword)));
+        }
+        let value = match self.check(TokenType::SEMICOLON) {
+            true => None,
+            false if self.function_kind == FunctionKind::Initializer => {
+                return Err(Errors::ReturnFromInitializer(Box::new(keyword)));
+            }
+            false => Some(self.expression()?),
+        };
+        self.consume(TokenType::SEMICOLON, Errors::MissingSemicolon)?;
+        Ok(Statment::ReturnStatment(keyword, value))
+    }
+
+    fn jump_statment(&mut self, keyword: Token) -> Result<Statment, Errors> {
+        if self.loops.is_empty() {
+            return Err(Errors::JumpOutsideLoop(Box::new(keyword)));
+        }
+        let label = self.match_token(TokenType::LABEL);
+        if let Some(label) = &label {
+            let is_enclosing = self
+                .loops
+                .iter()
+                .flatten()
+                .any(|enclosing| enclosing.text == label.text);
+            if !is_enclosing {
+                return Err(Errors::UndefinedLabel(Box::new(label.clone())));
+            }
+        }
+        self.consume(TokenType::SEMICOLON, Errors::MissingSemicolon)?;
+        match keyword.token_type {
+            TokenType::BREAK => Ok(Statment::BreakStatment(keyword, label)),
+            _ => Ok(Statment::ContinueStatment(keyword, label)),
+        }
+    }
+
+    // `'name: while (...)` or `'name: for (...)`
+    fn labelled_statment(&mut self, label: Token) -> Result<Statment, Errors> {
+        self.consume(TokenType::COLON, Errors::MissingLabelColon)?;
+        if self.match_token(TokenType::WHILE).is_some() {
+            return self.while_statment(Some(label));
+        }
+        if let Some(keyword) = self.match_token(TokenType::FOR) {
+            return self.for_statment(keyword, Some(label));
+        }
+        Err(Errors::LabelWithoutLoop(self.found()))
+    }
+
+    // the body of a loop, where `break` and `continue` are allowed
+    fn loop_body(&mut self, label: &Option<Token>) -> Result<Statment, Errors> {
+        self.loops.push(label.clone());
+        let body = self.statment();
+        self.loops.pop();
+        body
+    }
+
+    fn condition(&mut self) -> Result<Expression, Errors> {
+        let open = self.consume(TokenType::LeftPara, Errors::MissingCondition)?;
+        let condition = self.expression()?;
+        self.consume(TokenType::RightPara, |found| {
+            Errors::UntermitedCondition(found, open.span)
+        })?;
+        Ok(condition)
+    }
+
+    fn if_statment(&mut self) -> Result<Statment, Errors> {
+        let condition = self.condition()?;
+        let then_branch = Box::new(self.statment()?);
+        let else_branch = match self.match_token(TokenType::ELSE) {
+            Some(_) => Some(Box::new(self.statment()?)),
+            None => None,
+        };
+        Ok(Statment::IfStatment(condition, then_branch, else_branch))
+    }
+
+    fn while_statment(&mut self, label: Option<Token>) -> Result<Statment, Errors> {
+        let condition = self.condition()?;
+        let body = Box::new(self.loop_body(&label)?);
+        Ok(Statment::WhileStatment(condition, body, None, label))
+    }
+
+    // `for (init; cond; step) body` is desugared into
+    // `{ init; while (cond) body }` with `step` as the increment of the while
+    fn for_statment(&mut self, keyword: Token, label: Option<Token>) -> Result<Statment, Errors> {
+        let open = self.consume(TokenType::LeftPara, Errors::MissingCondition)?;
+
+        let initializer = if self.match_token(TokenType::SEMICOLON).is_some() {
+            None
+        } else if self.match_token(TokenType::LET).is_some() {
+            Some(self.let_declaration()?)
+        } else {
+            Some(self.expression_statment()?)
+        };
+
+        let condition = match self.check(TokenType::SEMICOLON) {
+            true => None,
+            false => Some(self.expression()?),
+        };
+        self.consume(TokenType::SEMICOLON, Errors::MissingSemicolon)?;
+
+        let increment = match self.check(TokenType::RightPara) {
+            true => None,
+            false => Some(Box::new(self.expression()?)),
+        };
+        self.consume(TokenType::RightPara, |found| {
+            Errors::UntermitedCondition(found, open.span)
+        })?;
+
+        let body = self.loop_body(&label)?;
+
+        let condition = condition.unwrap_or_else(|| {
+            Expression::new(
+                ExpressionKind::Literal(Token::new(
+                    TokenType::TRUE,
+                    "true".to_string(),
+                    None,
+                    keyword.span,
+                )),
+                keyword.span,
+            )
+        });
+        let mut body = Statment::WhileStatment(condition, Box::new(body), increment, label);
+
+        if let Some(initializer) = initializer {
+            body = Statment::BlockStatment(vec![initializer, body]);
+        }
+        Ok(body)
+    }
+
+    fn block(&mut self, open: Span) -> Result<Vec<Statment>, Errors> {
+        let mut statments = Vec::new();
+        while !self.check(TokenType::RightBrace) && !self.is_at_end() {
+            statments.push(self.declaration());
+        }
+        self.consume(TokenType::RightBrace, |found| {
+            Errors::UntermitedBlock(found, open)
+        })?;
+        Ok(statments)
+    }
+
+    fn print_statment(&mut self) -> Result<Statment, Errors> {
+        let exp = self.expression()?;
+        self.consume(TokenType::SEMICOLON, Errors::MissingSemicolon)?;
+        Ok(Statment::PrintStatment(exp))
+    }
+
+    fn expression_statment(&mut self) -> Result<Statment, Errors> {
+        let exp = self.expression()?;
+        self.consume(TokenType::SEMICOLON, Errors::MissingSemicolon)?;
+        Ok(Statment::ExpresisonStatment(exp))
+    }
+
+    pub fn expression(&mut self) -> Result<Expression, Errors> {
+        self.assignment()
+    }
+
+    fn assignment(&mut self) -> Result<Expression, Errors> {
+        let exp = self.conditional()?;
+
+        if self.match_token(TokenType::EQUAL).is_some() {
+            let target = exp.span;
+            let value = self.assignment()?;
+            let span = exp.span.merge(value.span);
+            return match exp.kind {
+                ExpressionKind::Variable(name) => Ok(Expression::new(
+                    ExpressionKind::Assign(name, Box::new(value)),
+                    span,
+                )),
+                ExpressionKind::Get(object, name) => Ok(Expression::new(
+                    ExpressionKind::Set(object, name, Box::new(value)),
+                    span,
+                )),
+                ExpressionKind::Index(object, bracket, index) => Ok(Expression::new(
+                    ExpressionKind::SetIndex(object, bracket, index, Box::new(value)),
+                    span,
+                )),
+                _ => Err(Errors::InvalidAssignmentTarget(target)),
+            };
+        }
+
+        if let Some(operator) = self.next_if(|token| {
+            [
+                TokenType::PlusEqual,
+                TokenType::MinusEqual,
+                TokenType::StarEqual,
+                TokenType::SlashEqual,
+            ]
+            .contains(&token.token_type)
+        }) {
+            let value = self.assignment()?;
+            let span = exp.span.merge(value.span);
+            return match exp.kind {
+                ExpressionKind::Variable(_)
+                | ExpressionKind::Get(..)
+                | ExpressionKind::Index(..) => Ok(Expression::new(
+                    ExpressionKind::CompoundAssign(Box::new(exp), operator, Box::new(value)),
+                    span,
+                )),
+                _ => Err(Errors::InvalidAssignmentTarget(exp.span)),
+            };
+        }
+        Ok(exp)
+    }
+
+    // groups to the right, so a ? b : c ? d : e is a ? b : (c ? d : e)
+    fn conditional(&mut self) -> Result<Expression, Errors> {
+        let condition = self.coalesce()?;
+
+        if let Some(question) = self.match_token(TokenType::QUESTION) {
+            let then = self.expression()?;
+            self.consume(TokenType::COLON, |found| {
+                Errors::MissingTernaryElse(found, question.span)
+            })?;
+            let otherwise = self.conditional()?;
+            let span = condition.span.merge(otherwise.span);
+            return Ok(Expression::new(
+                ExpressionKind::Conditional(
+                    Box::new(condition),
+                    Box::new(then),
+                    Box::new(otherwise),
+                ),
+                span,
+            ));
+        }
+        Ok(condition)
+    }
+
+    fn coalesce(&mut self) -> Result<Expression, Errors> {
+        let mut exp: Expression = self.or()?;
+
+        while let Some(token) = self.match_token(TokenType::QuestionQuestion) {
+            let expr_right = self.or()?;
+            let span = exp.span.merge(expr_right.span);
+            exp = Expression::new(
+                ExpressionKind::Logical(Box::new(exp), token, Box::new(expr_right)),
+                span,
+            )
+        }
+        Ok(exp)
+    }
+
+    fn or(&mut self) -> Result<Expression, Errors> {
+        let mut exp: Expression = self.and()?;
+
+        while let Some(token) = self.match_token(TokenType::OR) {
+            let expr_right = self.and()?;
+            let span = exp.span.merge(expr_right.span);
+            exp = Expression::new(
+                ExpressionKind::Logical(Box::new(exp), token, Box::new(expr_right)),
+                span,
+            )
+        }
+        Ok(exp)
+    }
+
+    fn and(&mut self) -> Result<Expression, Errors> {
+        let mut exp: Expression = self.eqality()?;
+
+        while let Some(token) = self.match_token(TokenType::AND) {
+            let expr_right = self.eqality()?;
+            let span = exp.span.merge(expr_right.span);
+            exp = Expression::new(
+                ExpressionKind::Logical(Box::new(exp), token, Box::new(expr_right)),
+                span,
+            )
+        }
+        Ok(exp)
     }
 
     fn eqality(&mut self) -> Result<Expression, Errors> {
         let mut exp: Expression = self.comp()?;
 
-        while let Some(token) = self.tokens.next_if(|token| {
+        while let Some(token) = self.next_if(|token| {
             [TokenType::BangEqual, TokenType::EqualEqual].contains(&token.token_type)
         }) {
             let expr_right = self.comp()?;
-            exp = Expression::Binary(Box::new(exp), token, Box::new(expr_right))
+            let span = exp.span.merge(expr_right.span);
+            exp = Expression::new(
+                ExpressionKind::Binary(Box::new(exp), token, Box::new(expr_right)),
+                span,
+            )
         }
-        return Ok(exp);
+        Ok(exp)
     }
 
     fn comp(&mut self) -> Result<Expression, Errors> {
-        let mut exp: Expression = self.term()?;
+        let mut exp: Expression = self.bit_or()?;
 
-        while let Some(token) = self.tokens.next_if(|token| {
+        while let Some(token) = self.next_if(|token| {
             [
                 TokenType::GreaterEqual,
                 TokenType::GREATER,
@@ -227,79 +1663,399 @@ impl Parser {
                 TokenType::LessEqual,
             ]
             .contains(&token.token_type)
+        }) {
+            let expr_right = self.bit_or()?;
+            let span = exp.span.merge(expr_right.span);
+            exp = Expression::new(
+                ExpressionKind::Binary(Box::new(exp), token, Box::new(expr_right)),
+                span,
+            )
+        }
+        Ok(exp)
+    }
+
+    fn bit_or(&mut self) -> Result<Expression, Errors> {
+        let mut exp: Expression = self.bit_xor()?;
+
+        while let Some(token) = self.match_token(TokenType::PIPE) {
+            let expr_right = self.bit_xor()?;
+            let span = exp.span.merge(expr_right.span);
+            exp = Expression::new(
+                ExpressionKind::Binary(Box::new(exp), token, Box::new(expr_right)),
+                span,
+            )
+        }
+        Ok(exp)
+    }
+
+    fn bit_xor(&mut self) -> Result<Expression, Errors> {
+        let mut exp: Expression = self.bit_and()?;
+
+        while let Some(token) = self.match_token(TokenType::CARET) {
+            let expr_right = self.bit_and()?;
+            let span = exp.span.merge(expr_right.span);
+            exp = Expression::new(
+                ExpressionKind::Binary(Box::new(exp), token, Box::new(expr_right)),
+                span,
+            )
+        }
+        Ok(exp)
+    }
+
+    fn bit_and(&mut self) -> Result<Expression, Errors> {
+        let mut exp: Expression = self.shift()?;
+
+        while let Some(token) = self.match_token(TokenType::AMPERSAND) {
+            let expr_right = self.shift()?;
+            let span = exp.span.merge(expr_right.span);
+            exp = Expression::new(
+                ExpressionKind::Binary(Box::new(exp), token, Box::new(expr_right)),
+                span,
+            )
+        }
+        Ok(exp)
+    }
+
+    fn shift(&mut self) -> Result<Expression, Errors> {
+        let mut exp: Expression = self.term()?;
+
+        while let Some(token) = self.next_if(|token| {
+            [TokenType::LessLess, TokenType::GreaterGreater].contains(&token.token_type)
         }) {
             let expr_right = self.term()?;
-            exp = Expression::Binary(Box::new(exp), token, Box::new(expr_right))
+            let span = exp.span.merge(expr_right.span);
+            exp = Expression::new(
+                ExpressionKind::Binary(Box::new(exp), token, Box::new(expr_right)),
+                span,
+            )
         }
-        return Ok(exp);
+        Ok(exp)
     }
 
     fn term(&mut self) -> Result<Expression, Errors> {
         let mut exp: Expression = self.factor()?;
 
-        while let Some(token) = self
-            .tokens
-            .next_if(|token| [TokenType::MINUS, TokenType::PLUS].contains(&token.token_type))
+        while let Some(token) =
+            self.next_if(|token| [TokenType::MINUS, TokenType::PLUS].contains(&token.token_type))
         {
             let expr_right = self.factor()?;
-            exp = Expression::Binary(Box::new(exp), token, Box::new(expr_right))
+            let span = exp.span.merge(expr_right.span);
+            exp = Expression::new(
+                ExpressionKind::Binary(Box::new(exp), token, Box::new(expr_right)),
+                span,
+            )
         }
-        return Ok(exp);
+        Ok(exp)
     }
 
     fn factor(&mut self) -> Result<Expression, Errors> {
         let mut exp: Expression = self.unary()?;
 
-        while let Some(token) = self
-            .tokens
-            .next_if(|token| [TokenType::SLASH, TokenType::STAR].contains(&token.token_type))
-        {
+        while let Some(token) = self.next_if(|token| {
+            [
+                TokenType::SLASH,
+                TokenType::STAR,
+                TokenType::PERCENT,
+                TokenType::SlashSlash,
+            ]
+            .contains(&token.token_type)
+        }) {
             let expr_right = self.unary()?;
-            exp = Expression::Binary(Box::new(exp), token, Box::new(expr_right))
+            let span = exp.span.merge(expr_right.span);
+            exp = Expression::new(
+                ExpressionKind::Binary(Box::new(exp), token, Box::new(expr_right)),
+                span,
+            )
         }
-        return Ok(exp);
+        Ok(exp)
     }
 
     fn unary(&mut self) -> Result<Expression, Errors> {
-        if let Some(operator) = self
-            .tokens
-            .next_if(|token| [TokenType::BANG, TokenType::MINUS].contains(&token.token_type))
-        {
+        if let Some(operator) = self.next_if(|token| {
+            [TokenType::BANG, TokenType::MINUS, TokenType::TILDE].contains(&token.token_type)
+        }) {
             let exp_right = self.unary()?;
-            return Ok(Expression::Unary(operator, Box::new(exp_right)));
+            let span = operator.span.merge(exp_right.span);
+            return Ok(Expression::new(
+                ExpressionKind::Unary(operator, Box::new(exp_right)),
+                span,
+            ));
         }
 
-        return Ok(self.primary()?);
+        self.power()
+    }
+
+    // binds tighter than unary minus and groups to the right, so -2 ** 2 ** 3 is -(2 ** (2 ** 3))
+    fn power(&mut self) -> Result<Expression, Errors> {
+        let exp = self.call()?;
+
+        if let Some(token) = self.match_token(TokenType::StarStar) {
+            let expr_right = self.unary()?;
+            let span = exp.span.merge(expr_right.span);
+            return Ok(Expression::new(
+                ExpressionKind::Binary(Box::new(exp), token, Box::new(expr_right)),
+                span,
+            ));
+        }
+        Ok(exp)
+    }
+
+    fn call(&mut self) -> Result<Expression, Errors> {
+        let mut exp = self.primary()?;
+        let mut optional = false;
+
+        loop {
+            if let Some(open) = self.match_token(TokenType::LeftPara) {
+                let mut arguments = Vec::new();
+                if !self.check(TokenType::RightPara) {
+                    loop {
+                        arguments.push(self.expression()?);
+                        if self.match_token(TokenType::COMMA).is_none() {
+                            break;
+                        }
+                    }
+                }
+                let paren = self.consume(TokenType::RightPara, |found| {
+                    Errors::UntermitedArgumentList(found, open.span)
+                })?;
+                let span = exp.span.merge(paren.span);
+                exp = Expression::new(ExpressionKind::Call(Box::new(exp), paren, arguments), span);
+            } else if self.match_token(TokenType::DOT).is_some() {
+                let name = self.consume(TokenType::IDENTIFIER, Errors::MissingPropertyName)?;
+                let span = exp.span.merge(name.span);
+                exp = Expression::new(ExpressionKind::Get(Box::new(exp), name), span);
+            } else if let Some(open) = self.match_token(TokenType::LeftBracket) {
+                exp = self.index(exp, open)?;
+            } else if self.match_token(TokenType::QuestionDot).is_some() {
+                let name = self.consume(TokenType::IDENTIFIER, Errors::MissingPropertyName)?;
+                let span = exp.span.merge(name.span);
+                exp = Expression::new(ExpressionKind::OptionalGet(Box::new(exp), name), span);
+                optional = true;
+            } else {
+                break;
+            }
+        }
+        // the rest of the chain after a `?.` is skipped along with it
+        if optional {
+            let span = exp.span;
+            exp = Expression::new(ExpressionKind::OptionalChain(Box::new(exp)), span);
+        }
+        Ok(exp)
     }
 
     fn primary(&mut self) -> Result<Expression, Errors> {
-        if let Some(token) = self.tokens.next() {
+        // anything else is left in place for `synchronize` to skip
+        let is_primary = |token: &Token| {
+            matches!(
+                token.token_type,
+                TokenType::TRUE
+                    | TokenType::FALSE
+                    | TokenType::NIL
+                    | TokenType::NUMBER
+                    | TokenType::STRING
+                    | TokenType::INTERPOLATION
+                    | TokenType::IDENTIFIER
+                    | TokenType::ERROR
+                    | TokenType::THIS
+                    | TokenType::SUPER
+                    | TokenType::LeftBracket
+                    | TokenType::LeftBrace
+                    | TokenType::LeftPara
+            )
+        };
+        if let Some(token) = self.next_if(is_primary) {
+            let span = token.span;
             match token.token_type {
                 TokenType::TRUE | TokenType::FALSE | TokenType::NIL => {
-                    return Ok(Expression::Literal(token));
+                    return Ok(Expression::new(ExpressionKind::Literal(token), span));
+                }
+
+                TokenType::NUMBER | TokenType::STRING => {
+                    return Ok(Expression::new(ExpressionKind::Literal(token), span));
+                }
+
+                TokenType::INTERPOLATION => return self.interpolation(token),
+
+                TokenType::IDENTIFIER => {
+                    return Ok(Expression::new(ExpressionKind::Variable(token), span));
+                }
+
+                TokenType::ERROR => {
+                    return Ok(Expression::new(ExpressionKind::Error(token), span));
+                }
+
+                TokenType::THIS => {
+                    if self.class_kind == ClassKind::None {
+                        return Err(Errors::ThisOutsideClass(Box::new(token)));
+                    }
+                    return Ok(Expression::new(ExpressionKind::This(token), span));
                 }
 
-                TokenType::NUMBER | TokenType::STRING => return Ok(Expression::Literal(token)),
+                TokenType::SUPER => {
+                    match self.class_kind {
+                        ClassKind::None => return Err(Errors::SuperOutsideClass(Box::new(token))),
+                        ClassKind::Class => {
+                            return Err(Errors::SuperWithoutSuperclass(Box::new(token)))
+                        }
+                        ClassKind::Subclass => {}
+                    }
+                    self.consume(TokenType::DOT, Errors::MissingSuperMethod)?;
+                    let method = self.consume(TokenType::IDENTIFIER, Errors::MissingSuperMethod)?;
+                    let span = span.merge(method.span);
+                    return Ok(Expression::new(ExpressionKind::Super(token, method), span));
+                }
+
+                TokenType::LeftBracket => {
+                    let mut elements = Vec::new();
+                    while !self.check(TokenType::RightBracket) {
+                        elements.push(self.expression()?);
+                        if self.match_token(TokenType::COMMA).is_none() {
+                            break;
+                        }
+                    }
+                    let bracket = self.consume(TokenType::RightBracket, |found| {
+                        Errors::UntermitedList(found, span)
+                    })?;
+                    let span = span.merge(bracket.span);
+                    return Ok(Expression::new(ExpressionKind::List(elements), span));
+                }
+
+                // a `{` that starts a statment is a block, anywhere else it is a Map
+                TokenType::LeftBrace => {
+                    let mut entries = Vec::new();
+                    while !self.check(TokenType::RightBrace) {
+                        let key = self.expression()?;
+                        self.consume(TokenType::COLON, Errors::MissingMapColon)?;
+                        entries.push((key, self.expression()?));
+                        if self.match_token(TokenType::COMMA).is_none() {
+                            break;
+                        }
+                    }
+                    let brace = self.consume(TokenType::RightBrace, |found| {
+                        Errors::UntermitedMap(found, span)
+                    })?;
+                    let span = span.merge(brace.span);
+                    return Ok(Expression::new(ExpressionKind::Map(token, entries), span));
+                }
 
                 TokenType::LeftPara => {
                     let exp = self.expression()?;
-                    if let Some(token) = self.tokens.next() {
-                        return Ok(Expression::Grouping(Box::new(exp)));
-                    } else {
-                        return Err(Errors::UntermitedGroup);
-                    }
+                    let paren = self.consume(TokenType::RightPara, |found| {
+                        Errors::UntermitedGroup(found, span)
+                    })?;
+                    let span = span.merge(paren.span);
+                    return Ok(Expression::new(
+                        ExpressionKind::Grouping(Box::new(exp)),
+                        span,
+                    ));
                 }
-                _ => return Err(Errors::NonPrimaryToken),
+                _ => unreachable!("only tokens that start a primary are taken"),
+            }
+        }
+        Err(Errors::NonPrimaryToken(self.found()))
+    }
+
+    // `[index]` or `[start:end]` after an expression, the `[` is already consumed
+    fn index(&mut self, object: Expression, open: Token) -> Result<Expression, Errors> {
+        let start = match self.check(TokenType::COLON) {
+            true => None,
+            false => Some(Box::new(self.expression()?)),
+        };
+        let is_slice = self.match_token(TokenType::COLON).is_some();
+        let end = match is_slice && !self.check(TokenType::RightBracket) {
+            true => Some(Box::new(self.expression()?)),
+            false => None,
+        };
+        let bracket = self.consume(TokenType::RightBracket, |found| {
+            Errors::UntermitedIndex(found, open.span)
+        })?;
+        let span = object.span.merge(bracket.span);
+        let kind = match (is_slice, start) {
+            (false, Some(index)) => ExpressionKind::Index(Box::new(object), open, index),
+            (_, start) => ExpressionKind::Slice(Box::new(object), open, start, end),
+        };
+        Ok(Expression::new(kind, span))
+    }
+
+    // the lexer splits "a ${b} c" into INTERPOLATION(a) b STRING(c),
+    // with one more INTERPOLATION for every extra `${`
+    fn interpolation(&mut self, head: Token) -> Result<Expression, Errors> {
+        let open = head.span;
+        let mut span = head.span;
+        let mut parts = vec![Expression::new(ExpressionKind::Literal(head), open)];
+        loop {
+            parts.push(self.expression()?);
+            let part = match self.next_if(|token| {
+                [TokenType::INTERPOLATION, TokenType::STRING].contains(&token.token_type)
+            }) {
+                Some(part) => part,
+                None => return Err(Errors::UntermitedInterpolation(self.found(), open)),
+            };
+            span = span.merge(part.span);
+            let is_last = part.token_type == TokenType::STRING;
+            let part_span = part.span;
+            parts.push(Expression::new(ExpressionKind::Literal(part), part_span));
+            if is_last {
+                break;
             }
         }
-        return Err(Errors::NonPrimaryToken);
+        Ok(Expression::new(ExpressionKind::Interpolation(parts), span))
+    }
+
+    fn check(&mut self, token_type: TokenType) -> bool {
+        self.tokens
+            .peek()
+            .is_some_and(|src_token| src_token.token_type == token_type)
     }
 
-    // fn check(&self, token_type: TokenType) -> bool {
-    //     self.tokens
-    //         .peek()
-    //         .is_some_and(|src_token| src_token.token_type == token_type)
-    // }
+    fn match_token(&mut self, token_type: TokenType) -> Option<Token> {
+        self.next_if(|token| token.token_type == token_type)
+    }
+
+    // every token is taken through here so the last one's span is known
+    fn next_if(&mut self, func: impl FnOnce(&Token) -> bool) -> Option<Token> {
+        let token = self.tokens.next_if(func)?;
+        self.previous = token.span;
+        self.consumed += 1;
+        match token.token_type {
+            TokenType::LeftBrace => self.braces += 1,
+            TokenType::RightBrace => self.braces = self.braces.saturating_sub(1),
+            _ => (),
+        }
+        Some(token)
+    }
+
+    // the error is built from whatever token was found instead
+    fn consume(
+        &mut self,
+        token_type: TokenType,
+        error: impl FnOnce(Box<Token>) -> Errors,
+    ) -> Result<Token, Errors> {
+        match self.match_token(token_type) {
+            Some(token) => Ok(token),
+            None => Err(error(self.found())),
+        }
+    }
+
+    fn found(&mut self) -> Box<Token> {
+        // the EOF is never taken, this only happens if the tokens did not end with one
+        let token = self.tokens.peek().cloned().unwrap_or_else(|| {
+            let end = Span {
+                start: self.previous.end,
+                end: self.previous.end,
+                start_line: self.previous.end_line,
+                start_column: self.previous.end_column,
+                end_line: self.previous.end_line,
+                end_column: self.previous.end_column,
+            };
+            Token::new(TokenType::EOF, String::new(), None, end)
+        });
+        Box::new(token)
+    }
+
+    fn is_at_end(&mut self) -> bool {
+        self.tokens.peek().is_none() || self.check(TokenType::EOF)
+    }
 }
 
 // struct Binary {
@@ -311,3 +2067,92 @@ impl Parser {
 // struct Uniary {}
 // struct Grouping {}
 // struct Literal {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lexer::Lexer;
+
+    fn parse(source: &str) -> (Program, ErrorSet) {
+        let (tokens, mut errors) = Lexer::new(source.to_owned()).scan_tokens();
+        let (program, parse_errors) = Parser::new(tokens).parse();
+        errors.append(parse_errors);
+        (program, errors)
+    }
+
+    fn errors(source: &str) -> ErrorSet {
+        parse(source).1
+    }
+
+    #[test]
+    fn valid_program_has_no_errors() {
+        let (program, errors) = parse("let x = 1;\nwhile (x < 3) { x = x + 1; }\nprint x;");
+        assert!(errors.is_empty());
+        assert_eq!(program.len(), 3);
+    }
+
+    #[test]
+    fn recovery_reports_every_bad_statment() {
+        let errors = errors("print 1 +; x = ; print \"a\";");
+        let errors: Vec<_> = errors.iter().collect();
+        assert_eq!(errors.len(), 2);
+        assert!(errors
+            .iter()
+            .all(|error| matches!(error, Errors::NonPrimaryToken(_))));
+        assert_eq!(errors[0].span().start_column, 10);
+        assert_eq!(errors[1].span().start_column, 16);
+    }
+
+    #[test]
+    fn missing_operand_at_end_of_file_points_past_the_operator() {
+        let errors = errors("print 1 +");
+        let errors: Vec<_> = errors.iter().collect();
+        assert_eq!(errors.len(), 1);
+        assert!(matches!(errors[0], Errors::NonPrimaryToken(_)));
+        assert_eq!(errors[0].span().start_column, 10);
+    }
+
+    #[test]
+    fn jump_outside_loop_keeps_its_block() {
+        let errors = errors("{ break }\n{ continue; }");
+        assert_eq!(errors.len(), 2);
+        assert!(errors
+            .iter()
+            .all(|error| matches!(error, Errors::JumpOutsideLoop(_))));
+    }
+
+    #[test]
+    fn brace_left_open_by_a_failed_statment_is_not_reported() {
+        for source in [
+            "fun f(a { print a; }",
+            "let x = 1; if (x { print 1; }",
+            "fun (a) {}",
+            "fun f(a { if (true) { print a; } }",
+            "{ fun f(a { print a; } }",
+        ] {
+            assert_eq!(errors(source).len(), 1, "{source}");
+        }
+    }
+
+    #[test]
+    fn stray_brace_is_reported() {
+        let errors = errors("print 1; }");
+        let errors: Vec<_> = errors.iter().collect();
+        assert_eq!(errors.len(), 1);
+        assert!(matches!(errors[0], Errors::NonPrimaryToken(_)));
+    }
+
+    #[test]
+    fn unclosed_block_points_at_its_brace() {
+        let errors = errors("print 1;\n{ print 2;");
+        let errors: Vec<_> = errors.iter().collect();
+        assert_eq!(errors.len(), 1);
+        match errors[0] {
+            Errors::UntermitedBlock(found, open) => {
+                assert_eq!(found.token_type, TokenType::EOF);
+                assert_eq!((open.start_line, open.start_column), (2, 1));
+            }
+            error => panic!("unexpected error {error}"),
+        }
+    }
+}
@@ -0,0 +1,196 @@
+use std::{
+    cmp::Ordering,
+    fmt::{self, Display},
+    ops::{Add, Mul, Neg, Sub},
+};
+
+use crate::bigint::BigInt;
+
+// an exact fraction, what dividing two integers gives in exact mode.
+// always kept in lowest terms with a positive denominator, so equal values
+// have equal fields
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Rational {
+    numerator: BigInt,
+    denominator: BigInt,
+}
+
+impl Rational {
+    /// `None` when the denominator is zero.
+    pub fn new(numerator: BigInt, denominator: BigInt) -> Option<Self> {
+        if denominator.is_zero() {
+            return None;
+        }
+        let gcd = numerator.gcd(&denominator);
+        let (mut numerator, _) = numerator.div_rem(&gcd)?;
+        let (mut denominator, _) = denominator.div_rem(&gcd)?;
+        if denominator.is_negative() {
+            numerator = -&numerator;
+            denominator = -&denominator;
+        }
+        Some(Rational {
+            numerator,
+            denominator,
+        })
+    }
+
+    pub fn numerator(&self) -> &BigInt {
+        &self.numerator
+    }
+
+    pub fn denominator(&self) -> &BigInt {
+        &self.denominator
+    }
+
+    pub fn is_integer(&self) -> bool {
+        self.denominator == BigInt::from(1)
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.numerator.is_zero()
+    }
+
+    pub fn to_f64(&self) -> f64 {
+        self.numerator.to_f64() / self.denominator.to_f64()
+    }
+
+    // the largest integer not above it
+    pub fn floor(&self) -> BigInt {
+        let (quotient, _) = self.numerator.div_rem_floor(&self.denominator).unwrap();
+        quotient
+    }
+
+    pub fn pow(&self, exponent: u32) -> Rational {
+        Rational {
+            numerator: self.numerator.pow(exponent),
+            denominator: self.denominator.pow(exponent),
+        }
+    }
+
+    /// `None` when dividing by zero.
+    pub fn checked_div(&self, other: &Rational) -> Option<Rational> {
+        Rational::new(
+            &self.numerator * &other.denominator,
+            &self.denominator * &other.numerator,
+        )
+    }
+}
+
+impl From<BigInt> for Rational {
+    fn from(value: BigInt) -> Self {
+        Rational {
+            numerator: value,
+            denominator: BigInt::from(1),
+        }
+    }
+}
+
+impl Ord for Rational {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // both denominators are positive so cross multiplying keeps the order
+        (&self.numerator * &other.denominator).cmp(&(&other.numerator * &self.denominator))
+    }
+}
+
+impl PartialOrd for Rational {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Neg for &Rational {
+    type Output = Rational;
+
+    fn neg(self) -> Rational {
+        Rational {
+            numerator: -&self.numerator,
+            denominator: self.denominator.clone(),
+        }
+    }
+}
+
+impl Add for &Rational {
+    type Output = Rational;
+
+    fn add(self, other: &Rational) -> Rational {
+        let numerator =
+            &(&self.numerator * &other.denominator) + &(&other.numerator * &self.denominator);
+        Rational::new(numerator, &self.denominator * &other.denominator).unwrap()
+    }
+}
+
+impl Sub for &Rational {
+    type Output = Rational;
+
+    fn sub(self, other: &Rational) -> Rational {
+        self + &-other
+    }
+}
+
+impl Mul for &Rational {
+    type Output = Rational;
+
+    fn mul(self, other: &Rational) -> Rational {
+        Rational::new(
+            &self.numerator * &other.numerator,
+            &self.denominator * &other.denominator,
+        )
+        .unwrap()
+    }
+}
+
+impl Display for Rational {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.numerator, self.denominator)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rational(numerator: i64, denominator: i64) -> Option<Rational> {
+        Rational::new(BigInt::from(numerator), BigInt::from(denominator))
+    }
+
+    #[test]
+    fn new_keeps_lowest_terms_and_a_positive_denominator() {
+        assert_eq!(rational(2, -4).unwrap().to_string(), "-1/2");
+        assert_eq!(rational(-3, -6).unwrap().to_string(), "1/2");
+        assert_eq!(rational(6, 3).unwrap().to_string(), "2/1");
+        assert_eq!(rational(0, -5).unwrap().to_string(), "0/1");
+        assert_eq!(rational(2, -4), rational(-1, 2));
+        assert!(rational(6, 3).unwrap().is_integer());
+    }
+
+    #[test]
+    fn zero_denominator_is_none() {
+        assert!(rational(1, 0).is_none());
+    }
+
+    #[test]
+    fn checked_div() {
+        let half = rational(1, 2).unwrap();
+        let three_quarters = rational(3, 4).unwrap();
+        assert_eq!(half.checked_div(&three_quarters), rational(2, 3));
+        assert!(half.checked_div(&rational(0, 1).unwrap()).is_none());
+    }
+
+    #[test]
+    fn floor_rounds_down() {
+        assert_eq!(rational(7, 2).unwrap().floor(), BigInt::from(3));
+        assert_eq!(rational(-1, 2).unwrap().floor(), BigInt::from(-1));
+        assert_eq!(rational(-4, 2).unwrap().floor(), BigInt::from(-2));
+    }
+
+    #[test]
+    fn arithmetic_and_order() {
+        let half = rational(1, 2).unwrap();
+        let third = rational(1, 3).unwrap();
+        assert_eq!(&half + &third, rational(5, 6).unwrap());
+        assert_eq!(&third - &half, rational(-1, 6).unwrap());
+        assert_eq!(&half * &third, rational(1, 6).unwrap());
+        assert!(-&half < third);
+        assert_eq!(rational(-2, 3).unwrap().pow(3), rational(-8, 27).unwrap());
+    }
+}